@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Archive extensions stripped before parsing, longest first
+const EXTENSIONS: &[&str] = &[
+    ".tar.gz", ".tar.xz", ".tar.bz2", ".tar.zst", ".tar.lz4", ".tar.lz", ".tgz", ".txz", ".tbz2",
+    ".tbz", ".tzst", ".tlz", ".tar", ".zip",
+];
+
+const OS_WORDS: &[&str] = &[
+    "linux", "darwin", "macos", "osx", "windows", "win", "win32", "win64", "freebsd", "openbsd",
+    "netbsd", "android",
+];
+
+const ARCH_WORDS: &[&str] = &[
+    "x64",
+    "x86_64",
+    "amd64",
+    "x86",
+    "i386",
+    "i686",
+    "arm64",
+    "aarch64",
+    "armv7l",
+    "armv7",
+    "armv6l",
+    "armhf",
+    "arm",
+    "riscv64",
+    "ppc64le",
+    "s390x",
+    "loongarch64",
+    "universal",
+];
+
+const LIBC_WORDS: &[&str] = &[
+    "gnu",
+    "glibc",
+    "musl",
+    "gnueabihf",
+    "musleabihf",
+    "gnueabi",
+    "musleabi",
+];
+
+/// Packaging words that carry no information about the app (e.g. the `_bin` in `jdk-21.0.2_linux-x64_bin`)
+const NOISE_WORDS: &[&str] = &[
+    "bin", "unknown", "pc", "static", "portable", "release", "dist",
+];
+
+/// Pre-release markers accepted as a separate segment right after a version (e.g. `app-1.2.0-rc1`)
+const PRE_RELEASE_WORDS: &[&str] = &["alpha", "beta", "rc", "pre", "preview", "dev", "nightly"];
+
+/// Whether a segment is a pre-release tag such as `rc1`, `beta` or `alpha.2`
+fn is_pre_release(word: &str) -> bool {
+    PRE_RELEASE_WORDS.iter().any(|tag| {
+        word.strip_prefix(tag)
+            .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit() || c == '.'))
+    })
+}
+
+/// A dotted numeric version with an optional pre-release tag, ordered numerically
+#[derive(Debug, Clone)]
+pub struct Version {
+    raw: String,
+    parts: Vec<u64>,
+    pre: Option<String>,
+}
+
+impl Version {
+    /// Parses `24.11.1`, `v0.13.0`, `2025.2.1.7`, `1.0.0rc1` or `21.0.2+13`; `None` if it does not start with a digit
+    pub fn parse(text: &str) -> Option<Self> {
+        let raw = text.strip_prefix(['v', 'V']).unwrap_or(text);
+        let core = raw.split('+').next().unwrap_or(raw);
+        if !core.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut parts = Vec::new();
+        let mut pre = None;
+        for segment in core.split('.') {
+            let digits_end = segment
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(segment.len());
+            if digits_end == 0 {
+                pre = Some(segment.to_string());
+                break;
+            }
+            parts.push(segment[..digits_end].parse().ok()?);
+            if digits_end < segment.len() {
+                pre = Some(segment[digits_end..].trim_start_matches('-').to_string());
+                break;
+            }
+        }
+
+        // A lone number glued to letters (`64bit`, `7zip`) is not a version
+        if parts.len() == 1 && pre.is_some() {
+            return None;
+        }
+
+        Some(Version {
+            raw: raw.to_string(),
+            parts,
+            pre,
+        })
+    }
+
+    fn with_pre_release(mut self, tag: &str) -> Self {
+        self.raw = format!("{}-{}", self.raw, tag);
+        self.pre = Some(tag.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        for i in 0..len {
+            let a = self.parts.get(i).copied().unwrap_or(0);
+            let b = other.parts.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        // A release sorts after any of its pre-releases
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+/// The components encoded in a release archive or folder name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveName {
+    pub name: String,
+    pub version: Option<Version>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub libc: Option<String>,
+}
+
+/// Splits a name on `-` and `_`, keeping each segment's byte offset
+/// `x86_64` is kept as one segment since its underscore is not a separator
+fn segments(name: &str) -> Vec<(usize, &str)> {
+    let mut raw = Vec::new();
+    let mut start = 0;
+    for (i, c) in name.char_indices() {
+        if c == '-' || c == '_' {
+            raw.push((start, &name[start..i]));
+            start = i + 1;
+        }
+    }
+    raw.push((start, &name[start..]));
+
+    let mut merged: Vec<(usize, &str)> = Vec::new();
+    for (offset, segment) in raw {
+        if segment == "64"
+            && let Some(last) = merged.last_mut()
+            && last.1.eq_ignore_ascii_case("x86")
+        {
+            *last = (last.0, &name[last.0..offset + segment.len()]);
+            continue;
+        }
+        if !segment.is_empty() {
+            merged.push((offset, segment));
+        }
+    }
+    merged
+}
+
+/// Parses names such as `node-v24.11.1-linux-x64`, `zig-linux-x86_64-0.13.0`,
+/// `jdk-21.0.2_linux-x64_bin` or `Telegram_4.16` into their components
+/// The app name is everything before the first version, OS, arch, libc or packaging segment
+pub fn parse(name: &str) -> ArchiveName {
+    let lower = name.to_ascii_lowercase();
+    let stem = EXTENSIONS
+        .iter()
+        .find_map(|ext| lower.strip_suffix(ext).map(|s| &name[..s.len()]))
+        .unwrap_or(name);
+
+    let mut parsed = ArchiveName {
+        name: String::new(),
+        version: None,
+        os: None,
+        arch: None,
+        libc: None,
+    };
+    let mut name_end = None;
+    let mut after_version = false;
+
+    for (index, (offset, segment)) in segments(stem).into_iter().enumerate() {
+        let word = segment.to_ascii_lowercase();
+        let word = word.as_str();
+        let was_after_version = std::mem::replace(&mut after_version, false);
+
+        // The first segment always belongs to the name, so `7zip` or `1password` stay intact
+        let classified = if index == 0 {
+            false
+        } else if let Some(version) = Version::parse(segment) {
+            if parsed.version.is_none() {
+                parsed.version = Some(version);
+                after_version = true;
+            }
+            true
+        } else if OS_WORDS.contains(&word) {
+            parsed.os.get_or_insert_with(|| word.to_string());
+            true
+        } else if ARCH_WORDS.contains(&word) {
+            parsed.arch.get_or_insert_with(|| word.to_string());
+            true
+        } else if LIBC_WORDS.contains(&word) {
+            parsed.libc.get_or_insert_with(|| word.to_string());
+            true
+        } else if was_after_version && is_pre_release(word) {
+            parsed.version = parsed.version.take().map(|v| v.with_pre_release(segment));
+            true
+        } else {
+            name_end.is_some() || NOISE_WORDS.contains(&word)
+        };
+
+        if classified && name_end.is_none() {
+            name_end = Some(offset);
+        }
+    }
+
+    let end = name_end.unwrap_or(stem.len());
+    parsed.name = stem[..end].trim_end_matches(['-', '_']).to_string();
+    parsed
+}
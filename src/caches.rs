@@ -0,0 +1,154 @@
+use crate::TarSmithError;
+use crate::desktop;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File in an applications directory mapping MIME types to the desktop entries that open them
+pub const MIME_CACHE: &str = "mimeinfo.cache";
+
+/// How deep below the applications directory entries are read; desktop IDs of nested files join
+/// the directories with `-`
+const MAX_DEPTH: usize = 4;
+
+/// Outcome of running an optional cache tool
+enum ToolRun {
+    Done,
+    Missing,
+    Failed(String),
+}
+
+/// Runs a cache tool if it is installed, without ever failing the caller
+fn run_tool(program: &str, args: &[&str], dir: &Path) -> ToolRun {
+    match Command::new(program).args(args).arg(dir).output() {
+        Ok(output) if output.status.success() => ToolRun::Done,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            ToolRun::Failed(
+                stderr
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| output.status.to_string()),
+            )
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => ToolRun::Missing,
+        Err(e) => ToolRun::Failed(e.to_string()),
+    }
+}
+
+/// Collects the `.desktop` files below `dir` with their desktop IDs, e.g. `kde-app.desktop` for
+/// `kde/app.desktop`
+fn desktop_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, TarSmithError> {
+    let mut files = Vec::new();
+    let mut pending = vec![(dir.to_path_buf(), String::new(), 0usize)];
+
+    while let Some((current, id_prefix, depth)) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                if depth < MAX_DEPTH {
+                    pending.push((path, format!("{}{}-", id_prefix, name), depth + 1));
+                }
+            } else if name.ends_with(".desktop") && path.is_file() {
+                files.push((format!("{}{}", id_prefix, name), path));
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Regenerates `mimeinfo.cache` in an applications directory from the `MimeType=` keys of its
+/// desktop entries, as `update-desktop-database` would; returns the number of MIME types listed
+pub fn update_mime_cache(applications_dir: &Path) -> Result<usize, TarSmithError> {
+    let mut handlers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (id, path) in desktop_files(applications_dir)? {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if desktop::entry_value(&contents, "Hidden").as_deref() == Some("true") {
+            continue;
+        }
+        let Some(mime_types) = desktop::entry_value(&contents, "MimeType") else {
+            continue;
+        };
+        for mime_type in mime_types.split(';').map(str::trim) {
+            if mime_type.contains('/') {
+                handlers
+                    .entry(mime_type.to_string())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+    }
+
+    let mut cache = String::from("[MIME Cache]\n");
+    for (mime_type, ids) in &mut handlers {
+        ids.sort();
+        ids.dedup();
+        cache.push_str(&format!("{}={};\n", mime_type, ids.join(";")));
+    }
+
+    let target = applications_dir.join(MIME_CACHE);
+    let staged = applications_dir.join(format!(".{}.tarsmith-new", MIME_CACHE));
+    fs::write(&staged, cache)?;
+    fs::rename(&staged, &target)?;
+    Ok(handlers.len())
+}
+
+/// Reports a cache tool's outcome; `missing` says what is stale without it
+fn report(program: &str, run: ToolRun, dir: &Path, missing: &str) {
+    match run {
+        ToolRun::Done => println!("    Ran {} on {} ✔", program, dir.display()),
+        ToolRun::Missing => println!("    {} not found, {}", program, missing),
+        ToolRun::Failed(reason) => {
+            println!(
+                "    Warning: {} failed on {}: {}",
+                program,
+                dir.display(),
+                reason
+            )
+        }
+    }
+}
+
+/// Brings launcher caches up to date after desktop entries or icons were written or removed,
+/// so menus and file managers pick up the change without logging in again
+/// Every step is best-effort: failures are reported but never undo the install or uninstall
+pub fn refresh(applications_dir: Option<&Path>, icon_theme_dir: Option<&Path>) {
+    if let Some(dir) = applications_dir.filter(|dir| dir.is_dir()) {
+        match update_mime_cache(dir) {
+            Ok(count) => println!(
+                "    Updated {} ({} MIME {}) ✔",
+                dir.join(MIME_CACHE).display(),
+                count,
+                if count == 1 { "type" } else { "types" }
+            ),
+            Err(e) => println!(
+                "    Warning: could not update {}: {}",
+                dir.join(MIME_CACHE).display(),
+                e
+            ),
+        }
+        report(
+            "update-desktop-database",
+            run_tool("update-desktop-database", &["-q"], dir),
+            dir,
+            "the launcher may only show up in menus after logging in again",
+        );
+    }
+
+    if let Some(dir) = icon_theme_dir.filter(|dir| dir.is_dir()) {
+        report(
+            "gtk-update-icon-cache",
+            run_tool("gtk-update-icon-cache", &["-q", "-t", "-f"], dir),
+            dir,
+            "icons may only update after logging in again",
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::TarSmithError;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Computes the lowercase hex SHA-256 digest of a file, streaming it in chunks
+pub fn sha256_file(path: &Path) -> Result<String, TarSmithError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Normalises a user-supplied hex digest, rejecting anything that is not 64 hex characters
+fn normalize_hex(hex: &str) -> Result<String, TarSmithError> {
+    let hex = hex.trim().to_ascii_lowercase();
+    if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hex)
+    } else {
+        Err(TarSmithError::InvalidChecksum(hex))
+    }
+}
+
+/// Compares an expected digest against the computed one
+pub fn verify_sha256(expected: &str, actual: &str) -> Result<(), TarSmithError> {
+    let expected = normalize_hex(expected)?;
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(TarSmithError::ChecksumMismatch {
+            expected,
+            actual: actual.to_string(),
+        })
+    }
+}
+
+/// Finds the archive's digest in a sums file
+/// Accepts GNU coreutils lines (`<hex>  <name>` or `<hex> *<name>`) and BSD lines (`SHA256 (<name>) = <hex>`)
+pub fn expected_from_sums_file(sums_file: &Path, archive: &Path) -> Result<String, TarSmithError> {
+    let archive_name = archive
+        .file_name()
+        .ok_or(TarSmithError::CannotFindArchiveName)?
+        .to_string_lossy()
+        .to_string();
+    let contents = fs::read_to_string(sums_file)?;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        let parsed = if let Some(rest) = line.strip_prefix("SHA256 (") {
+            rest.split_once(") = ")
+                .map(|(name, hex)| (hex.trim(), name))
+        } else {
+            line.split_once(char::is_whitespace)
+                .map(|(hex, name)| (hex, name.trim_start().trim_start_matches('*')))
+        };
+
+        let Some((hex, name)) = parsed else {
+            continue;
+        };
+        let name = Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name == archive_name {
+            return normalize_hex(hex);
+        }
+    }
+
+    Err(TarSmithError::ChecksumNotFound(
+        sums_file.to_path_buf(),
+        archive_name,
+    ))
+}
@@ -0,0 +1,171 @@
+use crate::TarSmithError;
+use crate::launcher;
+use crate::registry;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Why a PATH symlink cannot be created without affecting an existing command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// A regular file or directory that is not a TarSmith wrapper, such as the user's own script
+    NotSymlink,
+    /// A symlink or wrapper created by another TarSmith install
+    OtherApp(String),
+    /// A symlink or wrapper that launches something outside this app
+    ForeignLink(PathBuf),
+    /// A command of the same name found in another PATH directory
+    Shadows(PathBuf),
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::NotSymlink => write!(f, "an existing file that is not a symlink"),
+            Conflict::OtherApp(app) => write!(f, "a symlink installed for {}", app),
+            Conflict::ForeignLink(target) => write!(f, "a symlink to {}", target.display()),
+            Conflict::Shadows(command) => {
+                write!(f, "the same command at {}", command.display())
+            }
+        }
+    }
+}
+
+/// How a conflicting link is handled when nobody is asked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Replace or shadow the existing command (`--force`)
+    Force,
+    /// Create the link under another name (`--link-prefix`/`--link-suffix`)
+    Rename(String),
+    Skip,
+}
+
+/// Picks the resolution the command-line flags ask for; without any, conflicting links are skipped
+pub fn resolution(
+    name: &str,
+    force: bool,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+) -> Resolution {
+    if force {
+        Resolution::Force
+    } else if prefix.is_some() || suffix.is_some() {
+        Resolution::Rename(format!(
+            "{}{}{}",
+            prefix.unwrap_or(""),
+            name,
+            suffix.unwrap_or("")
+        ))
+    } else {
+        Resolution::Skip
+    }
+}
+
+/// What becomes of a conflicting link when the flags decide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Created at its own path anyway
+    Forced,
+    /// Created under the new name, which is free
+    Renamed(PathBuf),
+    /// Left out, as no flag resolves the conflict
+    Skipped,
+    /// Left out, as the new name is taken as well
+    RenameConflicts(PathBuf, Conflict),
+}
+
+/// Applies `resolution` to a conflicting `link`; a new name is checked once more, as flags give
+/// no other name to try
+pub fn resolve(
+    link: &Path,
+    resolution: Resolution,
+    app_name: &str,
+    app_dir: &Path,
+    is_user_level: bool,
+) -> Result<Outcome, TarSmithError> {
+    match resolution {
+        Resolution::Force => Ok(Outcome::Forced),
+        Resolution::Skip => Ok(Outcome::Skipped),
+        Resolution::Rename(new_name) => {
+            if !is_valid_link_name(&new_name) {
+                return Err(TarSmithError::InvalidSelection(format!(
+                    "Invalid command name: {}",
+                    new_name
+                )));
+            }
+            let renamed = link.with_file_name(&new_name);
+            Ok(
+                match find_conflict(&renamed, app_name, app_dir, is_user_level) {
+                    Some(conflict) => Outcome::RenameConflicts(renamed, conflict),
+                    None => Outcome::Renamed(renamed),
+                },
+            )
+        }
+    }
+}
+
+/// Checks whether creating `link` for `app_name` (installed under `app_dir`) would clobber or
+/// shadow an existing command; links already pointing into `app_dir` are the app's own
+pub fn find_conflict(
+    link: &Path,
+    app_name: &str,
+    app_dir: &Path,
+    is_user_level: bool,
+) -> Option<Conflict> {
+    if fs::symlink_metadata(link).is_ok() {
+        let Some(target) = launcher::target(link) else {
+            return Some(Conflict::NotSymlink);
+        };
+        if target.starts_with(app_dir) {
+            return None;
+        }
+
+        let owner = registry::load_records(is_user_level)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|record| {
+                record.app_name != app_name && record.symlinks.iter().any(|l| l == link)
+            });
+        return Some(match owner {
+            Some(record) => Conflict::OtherApp(record.app_name),
+            None => Conflict::ForeignLink(target),
+        });
+    }
+
+    let name = link.file_name()?;
+    let bin_dir = link.parent()?;
+    let canonical_bin_dir = fs::canonicalize(bin_dir).ok();
+    env::var_os("PATH").and_then(|path_var| {
+        env::split_paths(&path_var)
+            .filter(|dir| {
+                dir != bin_dir
+                    && (canonical_bin_dir.is_none()
+                        || fs::canonicalize(dir).ok() != canonical_bin_dir)
+            })
+            .map(|dir| dir.join(name))
+            .find(|command| is_executable(command))
+            .map(Conflict::Shadows)
+    })
+}
+
+/// Whether a path is a file with an execute bit, following symlinks
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return false;
+        }
+    }
+    metadata.is_file()
+}
+
+/// Whether a name can be used for a link directly inside the bin directory
+pub fn is_valid_link_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains('/')
+}
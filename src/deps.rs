@@ -0,0 +1,222 @@
+use crate::TarSmithError;
+use crate::elf;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Library directories the dynamic linker searches when ld.so.conf does not say otherwise
+const DEFAULT_LIB_DIRS: &[&str] = &["/lib", "/usr/lib", "/lib64", "/usr/lib64", "/usr/local/lib"];
+
+/// Reads the directories listed in an ld.so.conf file, following `include` globs of the form `dir/*.conf`
+fn read_ld_so_conf(path: &Path, dirs: &mut Vec<PathBuf>, depth: usize) {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return;
+    };
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(pattern) = line.strip_prefix("include") else {
+            dirs.push(PathBuf::from(line));
+            continue;
+        };
+        if depth > 4 {
+            continue;
+        }
+        let pattern = Path::new(pattern.trim());
+        let pattern = if pattern.is_relative() {
+            path.parent().unwrap_or(Path::new("/")).join(pattern)
+        } else {
+            pattern.to_path_buf()
+        };
+
+        let file_name = pattern.file_name().unwrap_or_default().to_string_lossy();
+        match file_name.split_once('*') {
+            Some((prefix, suffix)) => {
+                let Some(dir) = pattern.parent() else {
+                    continue;
+                };
+                let mut includes: Vec<PathBuf> = fs::read_dir(dir)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|p| {
+                        let name = p.file_name().unwrap_or_default().to_string_lossy();
+                        name.starts_with(prefix) && name.ends_with(suffix)
+                    })
+                    .collect();
+                includes.sort();
+                for include in includes {
+                    read_ld_so_conf(&include, dirs, depth + 1);
+                }
+            }
+            None => read_ld_so_conf(&pattern, dirs, depth + 1),
+        }
+    }
+}
+
+/// Returns the system linker search path: LD_LIBRARY_PATH, /etc/ld.so.conf, then the default
+/// directories including their multiarch subdirectories (e.g. /usr/lib/x86_64-linux-gnu)
+fn system_lib_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var("LD_LIBRARY_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    read_ld_so_conf(Path::new("/etc/ld.so.conf"), &mut dirs, 0);
+
+    for dir in DEFAULT_LIB_DIRS {
+        dirs.push(PathBuf::from(dir));
+        for triplet in multiarch_triplets() {
+            dirs.push(Path::new(dir).join(triplet));
+        }
+    }
+    dirs
+}
+
+/// Debian multiarch names of the host's library directories, which differ from the Rust
+/// architecture name on most targets (e.g. `i386-linux-gnu` for `x86`)
+fn multiarch_triplets() -> &'static [&'static str] {
+    let little_endian = cfg!(target_endian = "little");
+    match std::env::consts::ARCH {
+        "x86_64" => &["x86_64-linux-gnu"],
+        "x86" => &["i386-linux-gnu"],
+        "aarch64" => &["aarch64-linux-gnu"],
+        "arm" => &["arm-linux-gnueabihf", "arm-linux-gnueabi"],
+        "riscv64" => &["riscv64-linux-gnu"],
+        "powerpc64" if little_endian => &["powerpc64le-linux-gnu"],
+        "powerpc64" => &["powerpc64-linux-gnu"],
+        "powerpc" => &["powerpc-linux-gnu"],
+        "mips64" if little_endian => &["mips64el-linux-gnuabi64"],
+        "mips64" => &["mips64-linux-gnuabi64"],
+        "mips" if little_endian => &["mipsel-linux-gnu"],
+        "mips" => &["mips-linux-gnu"],
+        "s390x" => &["s390x-linux-gnu"],
+        "loongarch64" => &["loongarch64-linux-gnu"],
+        "sparc64" => &["sparc64-linux-gnu"],
+        _ => &[],
+    }
+}
+
+/// Collects the library directories an app bundles: every `lib` or `lib64` directory in its tree
+/// and their subdirectories
+fn bundled_lib_dirs(app_root: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![(app_root.to_path_buf(), false)];
+
+    while let Some((dir, inside_lib)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_lib = inside_lib || name == "lib" || name == "lib64";
+            if is_lib {
+                found.push(path.clone());
+            }
+            pending.push((path, is_lib));
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Lists the DT_NEEDED libraries of an executable that cannot be found in its RUNPATH,
+/// next to it, in the app's bundled library directories or on the system linker path
+/// Like the dynamic linker, files of another ELF class or machine are skipped, so a 32-bit
+/// `/usr/lib/libfoo.so.1` does not satisfy a 64-bit executable on a multilib host
+fn unresolved_libraries(exe: &Path, search_dirs: &[PathBuf]) -> Vec<String> {
+    let (Some(info), Some(target)) = (elf::dynamic_info(exe), elf::inspect(exe)) else {
+        return Vec::new();
+    };
+    let loadable = |path: &Path| {
+        elf::inspect(path).is_some_and(|lib| {
+            lib.machine == target.machine
+                && lib.is_64 == target.is_64
+                && lib.endian == target.endian
+        })
+    };
+    let origin = exe.parent().unwrap_or(Path::new("/"));
+    let origin_text = origin.to_string_lossy();
+
+    let mut dirs: Vec<PathBuf> = info
+        .search_paths
+        .iter()
+        .map(|p| {
+            PathBuf::from(
+                p.replace("${ORIGIN}", &origin_text)
+                    .replace("$ORIGIN", &origin_text),
+            )
+        })
+        .collect();
+    dirs.push(origin.to_path_buf());
+    dirs.extend(search_dirs.iter().cloned());
+
+    info.needed
+        .into_iter()
+        .filter(|lib| {
+            if lib.contains('/') {
+                !loadable(Path::new(lib))
+            } else {
+                !dirs.iter().any(|dir| loadable(&dir.join(lib)))
+            }
+        })
+        .collect()
+}
+
+/// Reports shared libraries the chosen executables need but that cannot be found
+/// With `strict`, missing libraries abort the install instead of only printing a warning
+pub fn check_shared_libraries(
+    executables: &[PathBuf],
+    app_root: &Path,
+    strict: bool,
+) -> Result<(), TarSmithError> {
+    println!("[8] Checking shared libraries...");
+
+    let mut search_dirs = bundled_lib_dirs(app_root);
+    search_dirs.extend(system_lib_dirs());
+
+    let mut missing: Vec<String> = Vec::new();
+    for exe in executables {
+        let unresolved = unresolved_libraries(exe, &search_dirs);
+        if unresolved.is_empty() {
+            continue;
+        }
+        println!(
+            "    {}: missing {}",
+            exe.file_name().unwrap_or_default().to_string_lossy(),
+            unresolved.join(", ")
+        );
+        for lib in unresolved {
+            if !missing.contains(&lib) {
+                missing.push(lib);
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        println!("[8] All shared libraries found ✔");
+        return Ok(());
+    }
+    if strict {
+        return Err(TarSmithError::MissingLibraries(missing.join(", ")));
+    }
+    println!(
+        "[8] Warning: {} shared {} not found; install the packages that provide {} before launching",
+        missing.len(),
+        if missing.len() == 1 {
+            "library"
+        } else {
+            "libraries"
+        },
+        if missing.len() == 1 { "it" } else { "them" }
+    );
+    Ok(())
+}
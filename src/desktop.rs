@@ -0,0 +1,779 @@
+use crate::TarSmithError;
+use crate::discover::normalize;
+use crate::tree::{self, AppTree};
+use std::path::{Component, Path, PathBuf};
+
+/// Version of the Desktop Entry Specification the generated files follow
+const SPEC_VERSION: &str = "1.5";
+
+/// Characters that force an `Exec` argument to be quoted
+const EXEC_RESERVED: &[char] = &[
+    ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')',
+    '`',
+];
+
+/// Field codes the spec allows after `%` in `Exec`
+const FIELD_CODES: &[char] = &['%', 'f', 'F', 'u', 'U', 'i', 'c', 'k'];
+
+/// Field codes the spec deprecates; readers ignore them and they are dropped from bundled `Exec` lines
+const DEPRECATED_FIELD_CODES: &[char] = &['d', 'D', 'n', 'N', 'v', 'm'];
+
+/// An application launcher written as a freedesktop `.desktop` file
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    pub name: String,
+    pub exec: PathBuf,
+    /// Icon theme name, or an absolute path to an image
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub categories: Vec<String>,
+}
+
+impl DesktopEntry {
+    /// An entry launching `exec` in the Utility category
+    pub fn new(name: &str, exec: &Path) -> Self {
+        DesktopEntry {
+            name: name.to_string(),
+            exec: exec.to_path_buf(),
+            icon: None,
+            terminal: false,
+            categories: vec!["Utility".to_string()],
+        }
+    }
+
+    /// Renders the entry, escaping every value and quoting `Exec`, then validates the result
+    pub fn render(&self) -> Result<String, TarSmithError> {
+        let exec = self.exec.to_string_lossy();
+        let mut contents = String::from("[Desktop Entry]\n");
+        let mut push = |key: &str, value: &str| {
+            contents.push_str(key);
+            contents.push('=');
+            contents.push_str(value);
+            contents.push('\n');
+        };
+
+        push("Version", SPEC_VERSION);
+        push("Type", "Application");
+        push("Name", &escape_string(&self.name));
+        push("Exec", &escape_string(&exec_path_arg(&exec)));
+        push("TryExec", &escape_string(&exec));
+        if let Some(icon) = &self.icon {
+            push("Icon", &escape_string(icon));
+        }
+        push("Terminal", if self.terminal { "true" } else { "false" });
+        if !self.categories.is_empty() {
+            push("Categories", &escape_list(&self.categories));
+        }
+
+        validate(&contents)?;
+        Ok(contents)
+    }
+}
+
+/// Escapes a `string`/`localestring` value: backslashes, control characters and a leading space
+fn escape_string(value: &str) -> String {
+    let mut escaped = String::new();
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ' ' if i == 0 => escaped.push_str("\\s"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes a list value such as `Categories`, terminating every item with `;`
+fn escape_list(items: &[String]) -> String {
+    items
+        .iter()
+        .map(|item| format!("{};", escape_string(item).replace(';', "\\;")))
+        .collect()
+}
+
+/// Quotes one `Exec` argument: reserved characters put it in double quotes with `"`, `` ` ``, `$`
+/// and `\` backslash-escaped
+/// The result still needs `escape_string`, which is what doubles those backslashes in the file
+fn quote_exec_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(EXEC_RESERVED) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes a literal path for `Exec`, doubling `%` so it is not read as a field code
+fn exec_path_arg(path: &str) -> String {
+    quote_exec_arg(&path.replace('%', "%%"))
+}
+
+/// Reverses `escape_string`
+fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Checks an `Exec` value (after string unescaping) for balanced quotes and valid field codes
+fn validate_exec(exec: &str) -> Result<(), String> {
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => match chars.next() {
+                Some('"' | '`' | '$' | '\\') => {}
+                _ => return Err("invalid escape inside a quoted Exec argument".to_string()),
+            },
+            '%' => match chars.next() {
+                Some(code)
+                    if FIELD_CODES.contains(&code) || DEPRECATED_FIELD_CODES.contains(&code) => {}
+                _ => return Err("lone % in Exec; a literal % must be written %%".to_string()),
+            },
+            _ => {}
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quote in Exec".to_string());
+    }
+    if exec.trim().is_empty() {
+        return Err("Exec is empty".to_string());
+    }
+    Ok(())
+}
+
+/// Validates a desktop file against the spec's structure and required keys:
+/// a leading `[Desktop Entry]` group, well-formed unique keys, `Type` and `Name`,
+/// and for applications a well-formed `Exec`
+pub fn validate(contents: &str) -> Result<(), TarSmithError> {
+    let invalid = |reason: String| TarSmithError::InvalidDesktopEntry(reason);
+
+    let mut group: Option<&str> = None;
+    let mut keys: Vec<(&str, &str)> = Vec::new();
+    for line in contents.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if group.is_none() && name != "Desktop Entry" {
+                return Err(invalid(format!("first group is [{}]", name)));
+            }
+            group = Some(name);
+            continue;
+        }
+        let Some(current_group) = group else {
+            return Err(invalid(format!("line before [Desktop Entry]: {}", line)));
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(invalid(format!("not a key=value line: {}", line)));
+        };
+        let key = key.trim_end();
+        let base_key = key.split_once('[').map_or(key, |(base, _)| base);
+        if base_key.is_empty()
+            || !base_key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(invalid(format!("invalid key: {}", key)));
+        }
+        if value.chars().any(|c| c.is_control()) {
+            return Err(invalid(format!(
+                "{} contains an unescaped control character",
+                key
+            )));
+        }
+        if current_group == "Desktop Entry" {
+            if keys.iter().any(|(k, _)| *k == key) {
+                return Err(invalid(format!("duplicate key: {}", key)));
+            }
+            keys.push((key, value.trim_start()));
+        }
+    }
+
+    if group.is_none() {
+        return Err(invalid("missing [Desktop Entry] group".to_string()));
+    }
+    let get = |wanted: &str| keys.iter().find(|(k, _)| *k == wanted).map(|(_, v)| *v);
+    for required in ["Type", "Name"] {
+        if get(required).is_none_or(|v| v.is_empty()) {
+            return Err(invalid(format!("missing required key {}", required)));
+        }
+    }
+    for boolean in ["Terminal", "NoDisplay", "Hidden"] {
+        if let Some(value) = get(boolean)
+            && value != "true"
+            && value != "false"
+        {
+            return Err(invalid(format!("{} must be true or false", boolean)));
+        }
+    }
+    if get("Type") == Some("Application") && get("DBusActivatable") != Some("true") {
+        let exec = get("Exec").ok_or_else(|| invalid("missing required key Exec".to_string()))?;
+        validate_exec(&unescape_string(exec)).map_err(invalid)?;
+    }
+    Ok(())
+}
+
+/// Splits an unescaped `Exec` value into its arguments, undoing the spec's quoting
+fn split_exec(exec: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_arg = true;
+            }
+            '\\' if in_quotes => current.push(chars.next()?),
+            ' ' | '\t' if !in_quotes => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            _ => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Some(args)
+}
+
+/// Looks up a key of the `[Desktop Entry]` group, unescaped
+pub fn entry_value(contents: &str, key: &str) -> Option<String> {
+    let mut in_entry = false;
+    for line in contents.lines() {
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_entry = group == "Desktop Entry";
+        } else if in_entry
+            && let Some((k, v)) = line.split_once('=')
+            && k.trim_end() == key
+        {
+            return Some(unescape_string(v.trim_start()));
+        }
+    }
+    None
+}
+
+/// Joins a relative path onto `base` and resolves `.` and `..` without touching the filesystem
+fn join_lexically(base: &Path, relative: &Path) -> PathBuf {
+    let mut joined = base.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::ParentDir => {
+                joined.pop();
+            }
+            Component::Normal(part) => joined.push(part),
+            _ => {}
+        }
+    }
+    joined
+}
+
+/// A `.desktop` file shipped inside the archive, reused in place of a generated entry
+#[derive(Debug, Clone)]
+pub struct BundledEntry {
+    /// Path of the file in the installed tree
+    pub source: PathBuf,
+    contents: String,
+    depth: usize,
+}
+
+impl BundledEntry {
+    /// Accepts a valid, visible application entry
+    fn new(source: PathBuf, contents: String, depth: usize) -> Option<Self> {
+        validate(&contents).ok()?;
+        if entry_value(&contents, "Type").as_deref() != Some("Application")
+            || entry_value(&contents, "Hidden").as_deref() == Some("true")
+            || entry_value(&contents, "NoDisplay").as_deref() == Some("true")
+        {
+            return None;
+        }
+        Some(BundledEntry {
+            source,
+            contents,
+            depth,
+        })
+    }
+
+    /// File name of the program the entry's `Exec` runs
+    pub fn program(&self) -> Option<String> {
+        let args = split_exec(&entry_value(&self.contents, "Exec")?)?;
+        let name = Path::new(args.first()?).file_name()?;
+        Some(name.to_string_lossy().to_string())
+    }
+
+    /// Ranks how likely this entry launches the app: 0 means it is about something else
+    /// - name: 30 if the file is named after the app (`kitty.desktop`, `org.blender.Blender.desktop`),
+    ///   20 if one name contains the other
+    /// - program: +15 if `Exec` runs the executable chosen for the desktop entry
+    /// - location: -1 per directory level
+    fn score(&self, app_name: &str, exec_name: &str) -> i32 {
+        let app = normalize(app_name);
+        let stem = self
+            .source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let last = normalize(stem.rsplit('.').next().unwrap_or_default());
+        let stem = normalize(&stem);
+
+        let mut score = if app.is_empty() || stem.is_empty() {
+            0
+        } else if stem == app || last == app {
+            30
+        } else if stem.contains(&app) || app.contains(&last) {
+            20
+        } else {
+            0
+        };
+        if self.program().is_some_and(|program| program == exec_name) {
+            score += 15;
+        }
+        if score == 0 {
+            0
+        } else {
+            score - self.depth as i32
+        }
+    }
+
+    /// Actions whose program is neither `exec` nor another installed executable, with the
+    /// program's name; `rewrite` leaves them out rather than have them launch the main program
+    pub fn dropped_actions(&self, exec: &Path, executables: &[PathBuf]) -> Vec<(String, String)> {
+        let mut dropped = Vec::new();
+        let mut action = None;
+        for line in self.contents.lines() {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                action = name.strip_prefix("Desktop Action ");
+                continue;
+            }
+            let (Some(id), Some((key, value))) = (action, line.split_once('=')) else {
+                continue;
+            };
+            if key.trim_end() != "Exec" {
+                continue;
+            }
+            let Some(args) = split_exec(&unescape_string(value.trim_start())) else {
+                continue;
+            };
+            let Some(program) = args.first().and_then(|p| Path::new(p).file_name()) else {
+                continue;
+            };
+            let installed = std::iter::once(exec)
+                .chain(executables.iter().map(PathBuf::as_path))
+                .any(|e| e.file_name() == Some(program));
+            if !installed {
+                dropped.push((id.to_string(), program.to_string_lossy().to_string()));
+            }
+        }
+        dropped
+    }
+
+    /// Rewrites the entry for the installed app, keeping every other key and group:
+    /// each `Exec` runs the installed executable of the same name (or `exec`), `TryExec` checks
+    /// `exec`, and `Icon` is the installed theme icon or a path resolved inside the app's tree
+    /// Actions listed by `dropped_actions` are removed along with their `Actions=` entries
+    pub fn rewrite(
+        &self,
+        exec: &Path,
+        executables: &[PathBuf],
+        icon: Option<&str>,
+    ) -> Result<String, TarSmithError> {
+        let source_dir = self.source.parent().unwrap_or(Path::new("/"));
+        let try_exec = escape_string(&exec.to_string_lossy());
+        let dropped: Vec<String> = self
+            .dropped_actions(exec, executables)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let is_dropped = |group: &str| {
+            group
+                .strip_prefix("Desktop Action ")
+                .is_some_and(|id| dropped.iter().any(|d| d == id))
+        };
+        let mut rewritten = String::new();
+        let mut group = "";
+        for line in self.contents.lines() {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                group = name;
+                if !is_dropped(group) {
+                    rewritten.push_str(line);
+                    rewritten.push('\n');
+                }
+                continue;
+            }
+            if is_dropped(group) {
+                continue;
+            }
+            let launcher_group = group == "Desktop Entry" || group.starts_with("Desktop Action ");
+            let Some((key, value)) = line.split_once('=').filter(|_| launcher_group) else {
+                rewritten.push_str(line);
+                rewritten.push('\n');
+                continue;
+            };
+
+            let value = value.trim_start();
+            match key.trim_end() {
+                "Exec" => {
+                    let exec_line = rewrite_exec(value, exec, executables).ok_or_else(|| {
+                        TarSmithError::InvalidDesktopEntry(format!("cannot parse Exec={}", value))
+                    })?;
+                    rewritten.push_str(&format!("Exec={}\n", exec_line));
+                    if group == "Desktop Entry" {
+                        rewritten.push_str(&format!("TryExec={}\n", try_exec));
+                    }
+                }
+                // Replaced next to `Exec`; D-Bus activation would need a service file that is not installed
+                "TryExec" | "DBusActivatable" => {}
+                "Actions" if group == "Desktop Entry" => {
+                    let kept: Vec<&str> = value
+                        .split(';')
+                        .filter(|id| !id.is_empty() && !dropped.iter().any(|d| d == id))
+                        .collect();
+                    if !kept.is_empty() {
+                        rewritten.push_str(&format!("Actions={};\n", kept.join(";")));
+                    }
+                }
+                "Icon" => {
+                    let icon = match icon {
+                        Some(name) => escape_string(name),
+                        None => {
+                            let original = unescape_string(value);
+                            if original.contains('/') && Path::new(&original).is_relative() {
+                                let resolved = join_lexically(source_dir, Path::new(&original));
+                                escape_string(&resolved.to_string_lossy())
+                            } else {
+                                value.to_string()
+                            }
+                        }
+                    };
+                    rewritten.push_str(&format!("Icon={}\n", icon));
+                }
+                _ => {
+                    rewritten.push_str(line);
+                    rewritten.push('\n');
+                }
+            }
+        }
+
+        // A dropped last group leaves the blank line that separated it
+        while rewritten.ends_with("\n\n") {
+            rewritten.pop();
+        }
+        validate(&rewritten)?;
+        Ok(rewritten)
+    }
+}
+
+/// Points an `Exec` value's program at an installed executable, keeping its arguments and field codes
+fn rewrite_exec(value: &str, exec: &Path, executables: &[PathBuf]) -> Option<String> {
+    let args = split_exec(&unescape_string(value))?;
+    let (program, rest) = args.split_first()?;
+    let name = Path::new(program).file_name()?;
+    let target = executables
+        .iter()
+        .find(|e| e.file_name() == Some(name))
+        .map_or(exec, PathBuf::as_path);
+
+    let mut rewritten = exec_path_arg(&target.to_string_lossy());
+    for arg in rest.iter().filter(|arg| {
+        !arg.strip_prefix('%').is_some_and(|code| {
+            code.len() == 1 && code.chars().all(|c| DEPRECATED_FIELD_CODES.contains(&c))
+        })
+    }) {
+        rewritten.push(' ');
+        rewritten.push_str(&quote_exec_arg(arg));
+    }
+    Some(escape_string(&rewritten))
+}
+
+/// Finds the `.desktop` file the app ships for itself, if any; `exec_name` is the file name of
+/// the executable chosen for the desktop entry
+pub fn find_bundled(
+    tree: &AppTree,
+    app_name: &str,
+    exec_name: &str,
+) -> Result<Option<BundledEntry>, TarSmithError> {
+    let candidates = tree
+        .files(&tree::DESKTOP_FILES)?
+        .into_iter()
+        .filter(|file| {
+            file.relative
+                .extension()
+                .is_some_and(|ext| ext == "desktop")
+        })
+        .filter_map(|file| {
+            let contents = String::from_utf8(file.header().into_owned()).ok()?;
+            BundledEntry::new(file.path, contents, file.depth)
+        })
+        .collect();
+    Ok(choose_bundled(candidates, app_name, exec_name))
+}
+
+/// Picks the best-scoring entry, breaking ties by path
+fn choose_bundled(
+    candidates: Vec<BundledEntry>,
+    app_name: &str,
+    exec_name: &str,
+) -> Option<BundledEntry> {
+    candidates
+        .into_iter()
+        .map(|entry| (entry.score(app_name, exec_name), entry))
+        .filter(|(score, _)| *score > 0)
+        .min_by(|(a, a_entry), (b, b_entry)| {
+            b.cmp(a).then_with(|| a_entry.source.cmp(&b_entry.source))
+        })
+        .map(|(_, entry)| entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line<'a>(contents: &'a str, key: &str) -> &'a str {
+        contents
+            .lines()
+            .find_map(|l| l.strip_prefix(&format!("{}=", key)))
+            .unwrap_or_else(|| panic!("no {} line", key))
+    }
+
+    #[test]
+    fn plain_paths_are_left_alone() {
+        let entry = DesktopEntry::new("firefox", Path::new("/opt/firefox/current/firefox"));
+        let contents = entry.render().unwrap();
+        assert_eq!(line(&contents, "Exec"), "/opt/firefox/current/firefox");
+        assert_eq!(line(&contents, "TryExec"), "/opt/firefox/current/firefox");
+        assert_eq!(line(&contents, "Name"), "firefox");
+        assert_eq!(line(&contents, "Categories"), "Utility;");
+    }
+
+    #[test]
+    fn spaces_are_quoted() {
+        let mut entry = DesktopEntry::new("My App", Path::new("/home/me/My Apps/app/bin/app"));
+        entry.icon = Some("/home/me/My Apps/app/icon.png".to_string());
+        let contents = entry.render().unwrap();
+        assert_eq!(line(&contents, "Exec"), "\"/home/me/My Apps/app/bin/app\"");
+        assert_eq!(line(&contents, "TryExec"), "/home/me/My Apps/app/bin/app");
+        assert_eq!(line(&contents, "Icon"), "/home/me/My Apps/app/icon.png");
+        assert_eq!(line(&contents, "Name"), "My App");
+    }
+
+    #[test]
+    fn percent_is_doubled_only_in_exec() {
+        let entry = DesktopEntry::new("app", Path::new("/opt/100%/app"));
+        let contents = entry.render().unwrap();
+        assert_eq!(line(&contents, "Exec"), "/opt/100%%/app");
+        assert_eq!(line(&contents, "TryExec"), "/opt/100%/app");
+    }
+
+    #[test]
+    fn quotes_dollars_and_backslashes_are_escaped() {
+        let entry = DesktopEntry::new("app", Path::new("/opt/a\"b$c`d\\e/app"));
+        let contents = entry.render().unwrap();
+        // Quoting escapes each as \x, then string escaping doubles every backslash
+        assert_eq!(line(&contents, "Exec"), r#""/opt/a\\"b\\$c\\`d\\\\e/app""#);
+        assert_eq!(line(&contents, "TryExec"), r#"/opt/a"b$c`d\\e/app"#);
+    }
+
+    #[test]
+    fn newlines_and_semicolons_cannot_inject_keys() {
+        let entry = DesktopEntry::new(
+            "evil\nExec=rm -rf ~;",
+            Path::new("/opt/x\nExec=/bin/false/app"),
+        );
+        let contents = entry.render().unwrap();
+        assert_eq!(
+            contents.lines().filter(|l| l.starts_with("Exec=")).count(),
+            1
+        );
+        assert_eq!(line(&contents, "Name"), "evil\\nExec=rm -rf ~;");
+        assert_eq!(line(&contents, "Exec"), "\"/opt/x\\nExec=/bin/false/app\"");
+    }
+
+    #[test]
+    fn leading_space_and_list_separators_are_escaped() {
+        let mut entry = DesktopEntry::new(" app", Path::new("/opt/app"));
+        entry.categories = vec!["Development".to_string(), "A;B".to_string()];
+        let contents = entry.render().unwrap();
+        assert_eq!(line(&contents, "Name"), "\\sapp");
+        assert_eq!(line(&contents, "Categories"), "Development;A\\;B;");
+    }
+
+    #[test]
+    fn validation_rejects_broken_entries() {
+        let valid = "[Desktop Entry]\nType=Application\nName=app\nExec=/opt/app\n";
+        assert!(validate(valid).is_ok());
+
+        for broken in [
+            "Type=Application\nName=app\nExec=/opt/app\n",
+            "[Other]\nType=Application\nName=app\nExec=/opt/app\n",
+            "[Desktop Entry]\nName=app\nExec=/opt/app\n",
+            "[Desktop Entry]\nType=Application\nExec=/opt/app\n",
+            "[Desktop Entry]\nType=Application\nName=app\n",
+            "[Desktop Entry]\nType=Application\nName=app\nExec=/opt/100%/app\n",
+            "[Desktop Entry]\nType=Application\nName=app\nExec=\"/opt/my app\n",
+            "[Desktop Entry]\nType=Application\nName=app\nName=other\nExec=/opt/app\n",
+            "[Desktop Entry]\nType=Application\nName=app\nExec=/opt/app\nTerminal=yes\n",
+            "[Desktop Entry]\nType=Application\nName=app\nExec=/opt/app\nBad Key=1\n",
+        ] {
+            assert!(validate(broken).is_err(), "accepted: {:?}", broken);
+        }
+    }
+
+    #[test]
+    fn unescape_reverses_escape() {
+        for value in ["plain", " lead", "a\\b", "two\nlines", "tab\there"] {
+            assert_eq!(unescape_string(&escape_string(value)), value);
+        }
+    }
+
+    const KITTY: &str = "[Desktop Entry]
+Version=1.0
+Type=Application
+Name=kitty
+Name[de]=Kitty-Terminal
+GenericName=Terminal emulator
+TryExec=kitty
+Exec=kitty %m --single-instance
+Icon=kitty
+Categories=System;TerminalEmulator;
+MimeType=text/plain;
+StartupWMClass=kitty
+Actions=new-window;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=kitten @ launch --type=os-window
+";
+
+    fn bundled(contents: &str) -> BundledEntry {
+        BundledEntry::new(
+            PathBuf::from("/opt/kitty/current/share/applications/kitty.desktop"),
+            contents.to_string(),
+            2,
+        )
+        .expect("valid bundled entry")
+    }
+
+    #[test]
+    fn bundled_entry_points_at_installed_executables() {
+        let exe = PathBuf::from("/opt/kitty/current/bin/kitty");
+        let kitten = PathBuf::from("/opt/kitty/current/bin/kitten");
+        let contents = bundled(KITTY)
+            .rewrite(&exe, &[exe.clone(), kitten.clone()], Some("kitty"))
+            .unwrap();
+
+        assert!(contents.contains("Exec=/opt/kitty/current/bin/kitty --single-instance\n"));
+        assert!(contents.contains("TryExec=/opt/kitty/current/bin/kitty\n"));
+        assert!(
+            contents.contains("Exec=/opt/kitty/current/bin/kitten @ launch --type=os-window\n")
+        );
+        assert_eq!(contents.matches("TryExec=").count(), 1);
+        for kept in [
+            "Name[de]=Kitty-Terminal",
+            "Categories=System;TerminalEmulator;",
+            "MimeType=text/plain;",
+            "StartupWMClass=kitty",
+            "Actions=new-window;",
+            "[Desktop Action new-window]",
+        ] {
+            assert!(contents.contains(kept), "lost {}", kept);
+        }
+    }
+
+    #[test]
+    fn bundled_entry_icon_and_field_codes() {
+        let entry = bundled(
+            "[Desktop Entry]\nType=Application\nName=Blender\nExec=\"/build/out dir/blender\" %F\nIcon=../../blender.svg\n",
+        );
+        let exe = PathBuf::from("/home/me/My Apps/blender/current/blender");
+        let contents = entry
+            .rewrite(&exe, std::slice::from_ref(&exe), None)
+            .unwrap();
+        assert!(contents.contains("Exec=\"/home/me/My Apps/blender/current/blender\" %F\n"));
+        assert!(contents.contains("Icon=/opt/kitty/current/blender.svg\n"));
+    }
+
+    #[test]
+    fn actions_of_missing_programs_are_dropped() {
+        let exe = PathBuf::from("/opt/kitty/current/bin/kitty");
+        let entry = bundled(&format!(
+            "{}\n[Desktop Action edit-config]\nName=Edit Config\nExec=kitty --edit-config\n",
+            KITTY.replace("Actions=new-window;", "Actions=new-window;edit-config;")
+        ));
+
+        assert_eq!(
+            entry.dropped_actions(&exe, std::slice::from_ref(&exe)),
+            [("new-window".to_string(), "kitten".to_string())]
+        );
+        let contents = entry
+            .rewrite(&exe, std::slice::from_ref(&exe), Some("kitty"))
+            .unwrap();
+        assert!(contents.contains("Actions=edit-config;\n"));
+        assert!(!contents.contains("[Desktop Action new-window]"));
+        assert!(!contents.contains("launch --type=os-window"));
+        assert!(contents.contains(
+            "StartupWMClass=kitty\nActions=edit-config;\n\n[Desktop Action edit-config]\nName=Edit Config\nExec=/opt/kitty/current/bin/kitty --edit-config\n"
+        ));
+
+        let only_missing = bundled(KITTY)
+            .rewrite(&exe, std::slice::from_ref(&exe), Some("kitty"))
+            .unwrap();
+        assert!(!only_missing.contains("Actions="));
+        assert!(only_missing.ends_with("StartupWMClass=kitty\n"));
+    }
+
+    #[test]
+    fn hidden_or_unrelated_entries_are_not_reused() {
+        let source = PathBuf::from("/opt/app/current/app.desktop");
+        let hidden = format!("{}\nNoDisplay=true\n", KITTY.split("\n\n").next().unwrap());
+        assert!(BundledEntry::new(source.clone(), hidden, 0).is_none());
+        assert!(
+            BundledEntry::new(
+                source,
+                "[Desktop Entry]\nType=Link\nName=x\nURL=y\n".into(),
+                0
+            )
+            .is_none()
+        );
+
+        let other = bundled(KITTY);
+        assert!(other.score("kitty", "kitty") > other.score("kitty", "kitten"));
+        assert_eq!(other.score("firefox", "firefox"), 0);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::TarSmithError;
+use crate::tree::{self, AppTree, TreeFile};
+use std::path::PathBuf;
+
+/// Helper programs shipped next to the real app that should never be offered as the app itself
+const HELPER_NAMES: &[&str] = &[
+    "chrome_crashpad_handler",
+    "crashpad_handler",
+    "chrome-sandbox",
+    "crashreporter",
+    "fsnotifier",
+    "restarter",
+    "minidump-analyzer",
+    "pingsender",
+    "glxtest",
+    "vaapitest",
+    "updater",
+    "update",
+    "uninstall",
+    "uninstaller",
+    "install",
+];
+
+/// Whether a file name is a shared library such as `libfoo.so` or `libfoo.so.1.2`
+fn is_shared_library(name: &str) -> bool {
+    name.ends_with(".so") || name.contains(".so.")
+}
+
+/// Whether a file name belongs to a known helper, including `*-uninstall`/`uninstall-*` variants
+fn is_helper(name: &str) -> bool {
+    let stem = name
+        .strip_suffix(".sh")
+        .unwrap_or(name)
+        .to_ascii_lowercase();
+    HELPER_NAMES.contains(&stem.as_str())
+        || stem.starts_with("uninstall")
+        || stem.ends_with("-uninstall")
+        || stem.ends_with("_uninstall")
+}
+
+/// Kind of program a file is, judged from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProgramKind {
+    Elf,
+    Script,
+    Other,
+}
+
+fn kind_from_header(header: &[u8]) -> ProgramKind {
+    if header.starts_with(b"\x7fELF") {
+        ProgramKind::Elf
+    } else if header.starts_with(b"#!") {
+        ProgramKind::Script
+    } else {
+        ProgramKind::Other
+    }
+}
+
+/// Lowercases a name and drops separators so `Android_Studio` and `android-studio` compare equal
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// A discovered executable and the facts it is ranked by
+struct Candidate {
+    path: PathBuf,
+    name: String,
+    depth: usize,
+    in_bin: bool,
+    kind: ProgramKind,
+    size: u64,
+}
+
+impl Candidate {
+    /// Ranks how likely this is the app's main executable: higher is better
+    /// - name: +50 if it equals the app name, +25 if one contains the other (ignoring case and separators)
+    /// - kind: +20 for ELF binaries, +5 for scripts, -20 for anything else; -10 more for `.sh` files
+    /// - size: +10 from 1 MiB, +5 from 100 KiB
+    /// - location: +10 at the root or directly in `bin/`, otherwise -5 per directory level
+    fn score(&self, app_name: &str) -> i32 {
+        let mut score = 0;
+
+        let name = normalize(self.name.strip_suffix(".sh").unwrap_or(&self.name));
+        let app = normalize(app_name);
+        if !app.is_empty() && name == app {
+            score += 50;
+        } else if !app.is_empty()
+            && !name.is_empty()
+            && (name.contains(&app) || app.contains(&name))
+        {
+            score += 25;
+        }
+
+        score += match self.kind {
+            ProgramKind::Elf => 20,
+            ProgramKind::Script => 5,
+            ProgramKind::Other => -20,
+        };
+        if self.name.ends_with(".sh") {
+            score -= 10;
+        }
+
+        if self.size >= 1024 * 1024 {
+            score += 10;
+        } else if self.size >= 100 * 1024 {
+            score += 5;
+        }
+
+        if self.depth == 0 || (self.depth == 1 && self.in_bin) {
+            score += 10;
+        } else {
+            score -= 5 * self.depth as i32;
+        }
+        score
+    }
+}
+
+/// Finds the app's executables anywhere in its tree, best match for `app_name` first
+/// Ties in score are broken by file name, then by path, so the order never depends on `read_dir`
+/// Shared libraries are never offered; helpers such as crash reporters and uninstall scripts,
+/// and `x`-bit files that are neither ELF nor scripts, are only offered when nothing else is found
+pub fn find_executables(tree: &AppTree, app_name: &str) -> Result<Vec<PathBuf>, TarSmithError> {
+    let candidates = tree
+        .files(&tree::EXECUTABLES)?
+        .into_iter()
+        .filter(TreeFile::is_executable)
+        .map(|file| Candidate {
+            name: file.name(),
+            depth: file.depth,
+            in_bin: file.in_bin(),
+            kind: kind_from_header(&file.header()),
+            size: file.size,
+            path: file.path,
+        })
+        .collect();
+    rank(candidates, app_name)
+}
+
+/// Filters and orders candidates as described on `find_executables`
+fn rank(candidates: Vec<Candidate>, app_name: &str) -> Result<Vec<PathBuf>, TarSmithError> {
+    let candidates: Vec<Candidate> = candidates
+        .into_iter()
+        .filter(|c| !is_shared_library(&c.name))
+        .collect();
+
+    let (mut preferred, fallback): (Vec<Candidate>, Vec<Candidate>) = candidates
+        .into_iter()
+        .partition(|c| !is_helper(&c.name) && c.kind != ProgramKind::Other);
+    if preferred.is_empty() {
+        preferred = fallback;
+    }
+    if preferred.is_empty() {
+        return Err(TarSmithError::NoExecutableFound);
+    }
+
+    preferred.sort_by(|a, b| {
+        b.score(app_name)
+            .cmp(&a.score(app_name))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    // Keep only the best-ranked file of each name, since PATH symlinks are named after it
+    let mut names = Vec::new();
+    Ok(preferred
+        .into_iter()
+        .filter(|c| {
+            let is_new = !names.contains(&c.name);
+            names.push(c.name.clone());
+            is_new
+        })
+        .map(|c| c.path)
+        .collect())
+}
+
+/// Matches a file name against a shell-style pattern supporting `*` and `?`
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Picks the executables whose file names match any of `patterns` (names or globs), keeping the ranked order
+/// Fails on the first pattern that matches nothing rather than silently falling back
+pub fn select_matching(
+    executables: &[PathBuf],
+    patterns: &[String],
+) -> Result<Vec<PathBuf>, TarSmithError> {
+    let name = |exe: &PathBuf| {
+        exe.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+
+    for pattern in patterns {
+        if !executables
+            .iter()
+            .any(|exe| glob_match(pattern, &name(exe)))
+        {
+            return Err(TarSmithError::NoMatchingExecutable {
+                pattern: pattern.clone(),
+                available: executables.iter().map(name).collect::<Vec<_>>().join(", "),
+            });
+        }
+    }
+
+    Ok(executables
+        .iter()
+        .filter(|exe| patterns.iter().any(|p| glob_match(p, &name(exe))))
+        .cloned()
+        .collect())
+}
@@ -0,0 +1,410 @@
+use crate::TarSmithError;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_STRSZ: u64 = 10;
+const DT_RPATH: u64 = 15;
+const DT_RUNPATH: u64 = 29;
+
+/// Byte order of an ELF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+/// What the ELF header says about where an executable can run
+#[derive(Debug, Clone)]
+pub struct ElfInfo {
+    pub machine: u16,
+    pub is_64: bool,
+    pub endian: Endian,
+    pub interpreter: Option<String>,
+}
+
+/// Shared libraries an executable links against, from its dynamic section
+#[derive(Debug, Clone, Default)]
+pub struct DynamicInfo {
+    /// DT_NEEDED sonames, in link order
+    pub needed: Vec<String>,
+    /// DT_RUNPATH or DT_RPATH directories, with `$ORIGIN` left unexpanded
+    pub search_paths: Vec<String>,
+}
+
+/// The fields of a program header TarSmith needs
+struct ProgramHeader {
+    kind: u32,
+    offset: u64,
+    vaddr: u64,
+    filesz: u64,
+}
+
+/// An opened ELF file with its parsed header
+struct ElfFile {
+    file: File,
+    info: ElfInfo,
+    headers: Vec<ProgramHeader>,
+}
+
+impl ElfFile {
+    fn read_at(&mut self, offset: u64, len: u64) -> Option<Vec<u8>> {
+        let mut buffer = vec![0u8; len.min(1 << 20) as usize];
+        self.file.seek(SeekFrom::Start(offset)).ok()?;
+        self.file.read_exact(&mut buffer).ok()?;
+        Some(buffer)
+    }
+
+    /// Maps a virtual address to a file offset through the loadable segments; `None` if the
+    /// segment bounds overflow, as only a crafted file's would
+    fn offset_of(&self, vaddr: u64) -> Option<u64> {
+        self.headers
+            .iter()
+            .filter(|h| h.kind == PT_LOAD)
+            .find(|h| {
+                vaddr >= h.vaddr && h.vaddr.checked_add(h.filesz).is_some_and(|end| vaddr < end)
+            })
+            .and_then(|h| (vaddr - h.vaddr).checked_add(h.offset))
+    }
+}
+
+impl ElfInfo {
+    /// Architecture name in the style of `uname -m`
+    pub fn arch(&self) -> String {
+        let name = match (self.machine, self.is_64, self.endian) {
+            (3, _, _) => "i386",
+            (62, _, _) => "x86_64",
+            (40, _, _) => "arm",
+            (183, _, _) => "aarch64",
+            (243, true, _) => "riscv64",
+            (243, false, _) => "riscv32",
+            (21, _, Endian::Little) => "ppc64le",
+            (21, _, Endian::Big) => "ppc64",
+            (20, _, _) => "ppc",
+            (22, _, _) => "s390x",
+            (8, true, _) => "mips64",
+            (8, false, _) => "mips",
+            (258, _, _) => "loongarch64",
+            (machine, _, _) => return format!("machine {}", machine),
+        };
+        name.to_string()
+    }
+
+    /// C library the executable expects, judged from its program interpreter
+    pub fn libc(&self) -> &'static str {
+        match &self.interpreter {
+            None => "static",
+            Some(interp) if interp.contains("musl") => "musl",
+            Some(_) => "glibc",
+        }
+    }
+}
+
+/// Reads `N` bytes at `offset` as an integer of the file's byte order
+fn read_uint<const N: usize>(bytes: &[u8], offset: usize, endian: Endian) -> Option<u64> {
+    let raw: [u8; N] = bytes.get(offset..offset + N)?.try_into().ok()?;
+    let mut value = 0u64;
+    for i in 0..N {
+        let byte = match endian {
+            Endian::Little => raw[N - 1 - i],
+            Endian::Big => raw[i],
+        };
+        value = (value << 8) | u64::from(byte);
+    }
+    Some(value)
+}
+
+/// Parses the identification and file header fields TarSmith needs from an ELF file's first
+/// 64 bytes: the header info and the offset, entry size and count of the program headers
+fn parse_header(header: &[u8]) -> Option<(ElfInfo, u64, u64, u64)> {
+    if header.get(..4)? != b"\x7fELF" {
+        return None;
+    }
+
+    let is_64 = match header.get(4)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let endian = match header.get(5)? {
+        1 => Endian::Little,
+        2 => Endian::Big,
+        _ => return None,
+    };
+    let machine = read_uint::<2>(header, 18, endian)? as u16;
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (
+            read_uint::<8>(header, 32, endian)?,
+            read_uint::<2>(header, 54, endian)?,
+            read_uint::<2>(header, 56, endian)?,
+        )
+    } else {
+        (
+            read_uint::<4>(header, 28, endian)?,
+            read_uint::<2>(header, 42, endian)?,
+            read_uint::<2>(header, 44, endian)?,
+        )
+    };
+    let info = ElfInfo {
+        machine,
+        is_64,
+        endian,
+        interpreter: None,
+    };
+    Some((info, phoff, phentsize, phnum))
+}
+
+/// Opens a file and parses its ELF header and program headers; `None` for anything that is not ELF,
+/// including headers whose offsets overflow
+fn open(path: &Path) -> Option<ElfFile> {
+    let mut file = File::open(path).ok()?;
+    let mut header = [0u8; 64];
+    file.read_exact(&mut header).ok()?;
+    let (info, phoff, phentsize, phnum) = parse_header(&header)?;
+    let endian = info.endian;
+    let is_64 = info.is_64;
+
+    let mut headers = Vec::new();
+    let mut entry = vec![0u8; phentsize as usize];
+    for index in 0..phnum {
+        let position = index
+            .checked_mul(phentsize)
+            .and_then(|offset| phoff.checked_add(offset))?;
+        file.seek(SeekFrom::Start(position)).ok()?;
+        file.read_exact(&mut entry).ok()?;
+        let kind = read_uint::<4>(&entry, 0, endian)? as u32;
+        let (offset, vaddr, filesz) = if is_64 {
+            (
+                read_uint::<8>(&entry, 8, endian)?,
+                read_uint::<8>(&entry, 16, endian)?,
+                read_uint::<8>(&entry, 32, endian)?,
+            )
+        } else {
+            (
+                read_uint::<4>(&entry, 4, endian)?,
+                read_uint::<4>(&entry, 8, endian)?,
+                read_uint::<4>(&entry, 16, endian)?,
+            )
+        };
+        headers.push(ProgramHeader {
+            kind,
+            offset,
+            vaddr,
+            filesz,
+        });
+    }
+
+    let mut elf = ElfFile {
+        file,
+        info,
+        headers,
+    };
+
+    if let Some((offset, size)) = elf
+        .headers
+        .iter()
+        .find(|h| h.kind == PT_INTERP)
+        .map(|h| (h.offset, h.filesz.min(4096)))
+    {
+        let path = elf.read_at(offset, size)?;
+        let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+        elf.info.interpreter = Some(String::from_utf8_lossy(&path[..end]).to_string());
+    }
+
+    Some(elf)
+}
+
+/// Parses the ELF header and program interpreter of a file; `None` for anything that is not ELF (e.g. scripts)
+pub fn inspect(path: &Path) -> Option<ElfInfo> {
+    open(path).map(|elf| elf.info)
+}
+
+/// Parses the start of an ELF file, such as the header kept in an archive listing; the program
+/// interpreter is not read
+pub fn inspect_header(header: &[u8]) -> Option<ElfInfo> {
+    parse_header(header).map(|(info, ..)| info)
+}
+
+/// Reads the DT_NEEDED and DT_RUNPATH/DT_RPATH entries of a dynamically linked ELF file
+/// Returns `None` for non-ELF files and an empty list for static executables
+pub fn dynamic_info(path: &Path) -> Option<DynamicInfo> {
+    let mut elf = open(path)?;
+    let Some((offset, size)) = elf
+        .headers
+        .iter()
+        .find(|h| h.kind == PT_DYNAMIC)
+        .map(|h| (h.offset, h.filesz))
+    else {
+        return Some(DynamicInfo::default());
+    };
+
+    let endian = elf.info.endian;
+    let word = if elf.info.is_64 { 8 } else { 4 };
+    let section = elf.read_at(offset, size)?;
+
+    let mut entries = Vec::new();
+    for chunk in section.chunks_exact(word * 2) {
+        let (tag, value) = if elf.info.is_64 {
+            (
+                read_uint::<8>(chunk, 0, endian)?,
+                read_uint::<8>(chunk, 8, endian)?,
+            )
+        } else {
+            (
+                read_uint::<4>(chunk, 0, endian)?,
+                read_uint::<4>(chunk, 4, endian)?,
+            )
+        };
+        if tag == DT_NULL {
+            break;
+        }
+        entries.push((tag, value));
+    }
+
+    let find = |wanted: u64| entries.iter().find(|(tag, _)| *tag == wanted).map(|e| e.1);
+    let (Some(strtab), Some(strsz)) = (find(DT_STRTAB), find(DT_STRSZ)) else {
+        return Some(DynamicInfo::default());
+    };
+    let strtab_offset = elf.offset_of(strtab)?;
+    let strings = elf.read_at(strtab_offset, strsz)?;
+    let string_at = |index: u64| -> Option<String> {
+        let bytes = strings.get(usize::try_from(index).ok()?..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+    };
+
+    let mut info = DynamicInfo::default();
+    for (tag, value) in &entries {
+        match *tag {
+            DT_NEEDED => info.needed.extend(string_at(*value)),
+            DT_RPATH | DT_RUNPATH => {
+                if let Some(paths) = string_at(*value) {
+                    info.search_paths
+                        .extend(paths.split(':').filter(|p| !p.is_empty()).map(String::from));
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// ELF machine number of the architecture TarSmith was built for
+fn host_machine() -> Option<u16> {
+    match std::env::consts::ARCH {
+        "x86" => Some(3),
+        "x86_64" => Some(62),
+        "arm" => Some(40),
+        "aarch64" => Some(183),
+        "riscv64" | "riscv32" => Some(243),
+        "powerpc64" => Some(21),
+        "powerpc" => Some(20),
+        "s390x" => Some(22),
+        "mips" | "mips64" => Some(8),
+        "loongarch64" => Some(258),
+        _ => None,
+    }
+}
+
+fn host_endian() -> Endian {
+    if cfg!(target_endian = "big") {
+        Endian::Big
+    } else {
+        Endian::Little
+    }
+}
+
+/// Whether an ELF file can run on this machine's architecture, word size and byte order
+fn runs_on_host(info: &ElfInfo) -> bool {
+    host_machine().is_none_or(|machine| machine == info.machine)
+        && info.is_64 == cfg!(target_pointer_width = "64")
+        && info.endian == host_endian()
+}
+
+/// Names an executable's target for mismatch messages, with its word size when that differs from the host's
+fn target_name(info: &ElfInfo) -> String {
+    if info.is_64 == cfg!(target_pointer_width = "64") {
+        info.arch()
+    } else {
+        format!("{} ({}-bit)", info.arch(), if info.is_64 { 64 } else { 32 })
+    }
+}
+
+/// Formats an executable's name with its architecture and C library for selection menus
+pub fn describe(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match inspect(path) {
+        Some(info) => format!("{} [{}, {}]", name, info.arch(), info.libc()),
+        None => format!("{} [script]", name),
+    }
+}
+
+/// What the architecture check found among an app's native executables
+#[derive(Debug, Default)]
+pub struct ArchCheck {
+    /// Architecture of the native executables, when every one of them runs here
+    pub matched: Option<String>,
+    /// Executables built for another machine, and program interpreters that are not installed
+    pub warnings: Vec<String>,
+}
+
+/// Checks the ELF executables against the host architecture and ABI
+/// Refuses the archive when none of its native executables can run here, unless `ignore` is set;
+/// otherwise warns about individual mismatches and missing program interpreters
+pub fn check_architecture(
+    native: &[(PathBuf, ElfInfo)],
+    ignore: bool,
+) -> Result<ArchCheck, TarSmithError> {
+    let mut check = ArchCheck::default();
+    if native.is_empty() {
+        return Ok(check);
+    }
+
+    let host = std::env::consts::ARCH;
+    let mismatched: Vec<&(PathBuf, ElfInfo)> = native
+        .iter()
+        .filter(|(_, info)| !runs_on_host(info))
+        .collect();
+
+    if mismatched.len() == native.len() && !ignore {
+        return Err(TarSmithError::ArchitectureMismatch {
+            found: target_name(&native[0].1),
+            host: host.to_string(),
+        });
+    }
+    for (exe, info) in &mismatched {
+        check.warnings.push(format!(
+            "{} is built for {}, this machine is {}",
+            exe.file_name().unwrap_or_default().to_string_lossy(),
+            target_name(info),
+            host
+        ));
+    }
+
+    for (exe, info) in native {
+        if let Some(interpreter) = &info.interpreter
+            && runs_on_host(info)
+            && !Path::new(interpreter).exists()
+        {
+            check.warnings.push(format!(
+                "{} needs {} ({}), which is not installed",
+                exe.file_name().unwrap_or_default().to_string_lossy(),
+                interpreter,
+                info.libc()
+            ));
+        }
+    }
+
+    if mismatched.is_empty() {
+        check.matched = Some(native[0].1.arch());
+    }
+    Ok(check)
+}
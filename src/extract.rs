@@ -0,0 +1,483 @@
+use crate::TarSmithError;
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use xz2::read::XzDecoder;
+
+/// Container or compression format of an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Gzip,
+    Xz,
+    Bzip2,
+    Zstd,
+    Lzip,
+    Lz4,
+    Tar,
+    Zip,
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ArchiveFormat::Gzip => "gzip",
+            ArchiveFormat::Xz => "xz",
+            ArchiveFormat::Bzip2 => "bzip2",
+            ArchiveFormat::Zstd => "zstd",
+            ArchiveFormat::Lzip => "lzip",
+            ArchiveFormat::Lz4 => "lz4",
+            ArchiveFormat::Tar => "tar",
+            ArchiveFormat::Zip => "zip",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ArchiveFormat {
+    /// Identifies the format from the leading bytes of the file
+    pub fn from_magic(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(ArchiveFormat::Gzip)
+        } else if header.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(ArchiveFormat::Xz)
+        } else if header.starts_with(b"BZh") {
+            Some(ArchiveFormat::Bzip2)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(ArchiveFormat::Zstd)
+        } else if header.starts_with(b"LZIP") {
+            Some(ArchiveFormat::Lzip)
+        } else if header.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+            Some(ArchiveFormat::Lz4)
+        } else if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Some(ArchiveFormat::Zip)
+        } else if header.len() >= 262 && &header[257..262] == b"ustar" {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the format from the archive's file extension, defaulting to plain tar
+    pub fn from_extension(archive: &Path) -> Self {
+        match archive.extension().and_then(|ext| ext.to_str()) {
+            Some("gz" | "tgz") => ArchiveFormat::Gzip,
+            Some("xz" | "txz") => ArchiveFormat::Xz,
+            Some("bz2" | "tbz" | "tbz2") => ArchiveFormat::Bzip2,
+            Some("zst" | "tzst") => ArchiveFormat::Zstd,
+            Some("lz" | "tlz") => ArchiveFormat::Lzip,
+            Some("lz4") => ArchiveFormat::Lz4,
+            Some("zip") => ArchiveFormat::Zip,
+            _ => ArchiveFormat::Tar,
+        }
+    }
+}
+
+/// Sniffs the archive's magic bytes, falling back to its extension when they are not recognised
+/// Returns the format and whether it came from the file contents
+pub fn detect_format(archive: &Path) -> Result<(ArchiveFormat, bool), TarSmithError> {
+    let mut header = Vec::with_capacity(262);
+    File::open(archive)?.take(262).read_to_end(&mut header)?;
+
+    Ok(match ArchiveFormat::from_magic(&header) {
+        Some(format) => (format, true),
+        None => (ArchiveFormat::from_extension(archive), false),
+    })
+}
+
+/// Wraps the archive file in the decoder matching its format
+fn open_decoder(archive: &Path, format: ArchiveFormat) -> Result<Box<dyn Read>, TarSmithError> {
+    let file = BufReader::new(File::open(archive)?);
+
+    Ok(match format {
+        ArchiveFormat::Gzip => Box::new(MultiGzDecoder::new(file)),
+        ArchiveFormat::Xz => Box::new(XzDecoder::new_multi_decoder(file)),
+        ArchiveFormat::Bzip2 => Box::new(MultiBzDecoder::new(file)),
+        ArchiveFormat::Zstd => {
+            Box::new(zstd::stream::read::Decoder::with_buffer(file).map_err(|e| {
+                TarSmithError::ExtractionFailed(format!("{} archive: {}", format, e))
+            })?)
+        }
+        ArchiveFormat::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(file)),
+        ArchiveFormat::Tar => Box::new(file),
+        ArchiveFormat::Lzip | ArchiveFormat::Zip => {
+            return Err(TarSmithError::UnsupportedFormat(format.to_string()));
+        }
+    })
+}
+
+/// Setuid, setgid and sticky bits, never kept from an archive: a setuid file extracted by a
+/// `--system` install would run as root for every user
+const SPECIAL_MODE_BITS: u32 = 0o7000;
+
+/// Extracts an archive into `dest` without relying on a `tar` binary
+pub fn extract_archive(archive: &Path, dest: &Path) -> Result<(), TarSmithError> {
+    let (format, from_magic) = detect_format(archive)?;
+    println!(
+        "[3] Detected format: {} (from file {}) ✔",
+        format,
+        if from_magic { "contents" } else { "extension" }
+    );
+
+    if format == ArchiveFormat::Zip {
+        return extract_zip(archive, dest);
+    }
+
+    let reader = open_decoder(archive, format)?;
+    let failed =
+        |e: io::Error| TarSmithError::ExtractionFailed(format!("{} archive: {}", format, e));
+
+    let mut tar = tar::Archive::new(reader);
+    tar.set_preserve_permissions(true);
+    tar.set_mask(SPECIAL_MODE_BITS);
+    tar.set_preserve_mtime(true);
+    tar.set_overwrite(true);
+
+    for entry in tar.entries().map_err(failed)? {
+        let mut entry = entry.map_err(failed)?;
+        let entry_path = entry.path().map_err(failed)?.into_owned();
+        let relative = sanitize_entry_path(&entry_path)?;
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let target = entry
+                .link_name()
+                .map_err(failed)?
+                .ok_or_else(|| TarSmithError::UnsafeArchivePath(entry_path.clone()))?
+                .into_owned();
+            if entry_type.is_symlink() {
+                check_symlink_target(&relative, &target)?;
+            } else {
+                sanitize_entry_path(&target)?;
+            }
+        }
+
+        entry
+            .unpack_in(dest)
+            .map_err(|e| TarSmithError::EntryExtractionFailed(entry_path, e.to_string()))?;
+    }
+
+    check_extracted_symlinks(dest)
+}
+
+/// Number of leading bytes kept for each listed file, enough to tell ELF binaries from scripts
+/// and to read the pixel size of PNG and XPM icons
+pub const LISTED_HEADER_LEN: u64 = 256;
+
+/// Bundled `.desktop` files are kept whole, up to this size, so they can be rewritten in a plan
+pub const LISTED_DESKTOP_FILE_LEN: u64 = 64 * 1024;
+
+/// How much of a listed file's contents is kept
+pub fn listed_len(path: &Path) -> u64 {
+    if path.extension().is_some_and(|ext| ext == "desktop") {
+        LISTED_DESKTOP_FILE_LEN
+    } else {
+        LISTED_HEADER_LEN
+    }
+}
+
+/// What an archive entry would be extracted as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListedKind {
+    File,
+    Dir,
+    Symlink(PathBuf),
+    Other,
+}
+
+/// One archive entry as read from the archive's headers, without writing anything to disk
+#[derive(Debug, Clone)]
+pub struct ListedEntry {
+    /// Sanitized path relative to the extraction root
+    pub path: PathBuf,
+    pub kind: ListedKind,
+    pub mode: u32,
+    pub size: u64,
+    /// First bytes of a regular file's contents, or all of a `.desktop` file
+    pub header: Vec<u8>,
+}
+
+/// Lists an archive's entries by streaming through it, applying the same path and symlink
+/// checks as extraction; hard links are listed as copies of the file they link to
+pub fn list_archive(archive: &Path) -> Result<(ArchiveFormat, Vec<ListedEntry>), TarSmithError> {
+    let (format, _) = detect_format(archive)?;
+    if format == ArchiveFormat::Zip {
+        return Ok((format, list_zip(archive)?));
+    }
+
+    let reader = open_decoder(archive, format)?;
+    let failed =
+        |e: io::Error| TarSmithError::ExtractionFailed(format!("{} archive: {}", format, e));
+
+    let mut tar = tar::Archive::new(reader);
+    let mut listed: Vec<ListedEntry> = Vec::new();
+    for entry in tar.entries().map_err(failed)? {
+        let mut entry = entry.map_err(failed)?;
+        let entry_path = entry.path().map_err(failed)?.into_owned();
+        let path = sanitize_entry_path(&entry_path)?;
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        let entry_type = entry.header().entry_type();
+        let mode = entry.header().mode().unwrap_or(0o644);
+        let size = entry.header().size().unwrap_or(0);
+        let link = || -> Result<PathBuf, TarSmithError> {
+            Ok(entry
+                .link_name()
+                .map_err(failed)?
+                .ok_or_else(|| TarSmithError::UnsafeArchivePath(entry_path.clone()))?
+                .into_owned())
+        };
+
+        let listed_entry = if entry_type.is_symlink() {
+            let target = link()?;
+            check_symlink_target(&path, &target)?;
+            ListedEntry {
+                path,
+                kind: ListedKind::Symlink(target),
+                mode,
+                size: 0,
+                header: Vec::new(),
+            }
+        } else if entry_type.is_hard_link() {
+            let target = sanitize_entry_path(&link()?)?;
+            match listed.iter().find(|e| e.path == target) {
+                Some(original) => ListedEntry {
+                    path,
+                    ..original.clone()
+                },
+                None => ListedEntry {
+                    path,
+                    kind: ListedKind::File,
+                    mode,
+                    size: 0,
+                    header: Vec::new(),
+                },
+            }
+        } else if entry_type.is_dir() {
+            ListedEntry {
+                path,
+                kind: ListedKind::Dir,
+                mode,
+                size: 0,
+                header: Vec::new(),
+            }
+        } else if entry_type.is_file() {
+            let mut header = Vec::new();
+            (&mut entry)
+                .take(listed_len(&path))
+                .read_to_end(&mut header)
+                .map_err(failed)?;
+            ListedEntry {
+                path,
+                kind: ListedKind::File,
+                mode,
+                size,
+                header,
+            }
+        } else {
+            ListedEntry {
+                path,
+                kind: ListedKind::Other,
+                mode,
+                size,
+                header: Vec::new(),
+            }
+        };
+        listed.push(listed_entry);
+    }
+
+    Ok((format, listed))
+}
+
+/// Lists a zip archive's entries from its central directory, reading only the start of each file
+fn list_zip(archive: &Path) -> Result<Vec<ListedEntry>, TarSmithError> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| TarSmithError::ExtractionFailed(format!("zip archive: {}", e)))?;
+
+    let mut listed = Vec::new();
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| TarSmithError::ExtractionFailed(format!("zip archive: {}", e)))?;
+        let entry_name = PathBuf::from(entry.name());
+        let failed =
+            |e: io::Error| TarSmithError::EntryExtractionFailed(entry_name.clone(), e.to_string());
+
+        let path = sanitize_entry_path(&entry_name)?;
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+        let mode = entry.unix_mode().unwrap_or(0o644);
+        let size = entry.size();
+
+        let kind = if entry.is_dir() {
+            ListedKind::Dir
+        } else if entry.is_symlink() {
+            let mut target = String::new();
+            entry.read_to_string(&mut target).map_err(failed)?;
+            check_symlink_target(&path, Path::new(&target))?;
+            ListedKind::Symlink(PathBuf::from(target))
+        } else {
+            ListedKind::File
+        };
+
+        let mut header = Vec::new();
+        if kind == ListedKind::File {
+            (&mut entry)
+                .take(listed_len(&path))
+                .read_to_end(&mut header)
+                .map_err(failed)?;
+        }
+        listed.push(ListedEntry {
+            path,
+            kind,
+            mode,
+            size,
+            header,
+        });
+    }
+
+    Ok(listed)
+}
+
+/// Extracts a zip archive into `dest`, restoring Unix permission bits and symlinks
+/// stored in the entries' external attributes
+fn extract_zip(archive: &Path, dest: &Path) -> Result<(), TarSmithError> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| TarSmithError::ExtractionFailed(format!("zip archive: {}", e)))?;
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| TarSmithError::ExtractionFailed(format!("zip archive: {}", e)))?;
+        let entry_name = PathBuf::from(entry.name());
+        let failed =
+            |e: io::Error| TarSmithError::EntryExtractionFailed(entry_name.clone(), e.to_string());
+
+        let relative = sanitize_entry_path(&entry_name)?;
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let out_path = dest.join(&relative);
+
+        if entry.is_dir() {
+            ensure_no_symlinks(dest, &relative, &entry_name)?;
+            fs::create_dir_all(&out_path).map_err(failed)?;
+            continue;
+        }
+        if let Some(parent) = relative.parent() {
+            ensure_no_symlinks(dest, parent, &entry_name)?;
+            fs::create_dir_all(dest.join(parent)).map_err(failed)?;
+        }
+        if fs::symlink_metadata(&out_path).is_ok_and(|m| m.file_type().is_symlink()) {
+            fs::remove_file(&out_path).map_err(failed)?;
+        }
+
+        #[cfg(unix)]
+        if entry.is_symlink() {
+            let mut target = String::new();
+            entry.read_to_string(&mut target).map_err(failed)?;
+            check_symlink_target(&relative, Path::new(&target))?;
+            std::os::unix::fs::symlink(&target, &out_path).map_err(failed)?;
+            continue;
+        }
+
+        let mut out_file = File::create(&out_path).map_err(failed)?;
+        io::copy(&mut entry, &mut out_file).map_err(failed)?;
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&out_path, fs::Permissions::from_mode(mode & 0o777))
+                .map_err(failed)?;
+        }
+    }
+
+    check_extracted_symlinks(dest)
+}
+
+/// Normalises an archive entry path, rejecting absolute paths and `..` components
+fn sanitize_entry_path(path: &Path) -> Result<PathBuf, TarSmithError> {
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => clean.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(TarSmithError::UnsafeArchivePath(path.to_path_buf()));
+            }
+        }
+    }
+    Ok(clean)
+}
+
+/// Ensures a symlink stored at `entry` (relative to the extraction root) stays inside that root
+fn check_symlink_target(entry: &Path, target: &Path) -> Result<(), TarSmithError> {
+    let escapes = || TarSmithError::UnsafeSymlink(entry.to_path_buf(), target.to_path_buf());
+
+    let mut resolved: Vec<_> = entry
+        .parent()
+        .map(|parent| parent.components().collect())
+        .unwrap_or_default();
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop().ok_or_else(escapes)?;
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(escapes()),
+        }
+    }
+    Ok(())
+}
+
+/// Fails if any existing component of `relative` below `root` is a symlink, so that
+/// creating directories or files there cannot follow (possibly chained) links out of the root
+fn ensure_no_symlinks(root: &Path, relative: &Path, entry: &Path) -> Result<(), TarSmithError> {
+    let mut path = root.to_path_buf();
+    for component in relative.components() {
+        path.push(component);
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                return Err(TarSmithError::UnsafeArchivePath(entry.to_path_buf()));
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+    Ok(())
+}
+
+/// Walks the extracted tree and rejects any symlink that resolves outside it,
+/// catching chains of individually harmless links
+fn check_extracted_symlinks(root: &Path) -> Result<(), TarSmithError> {
+    let canonical_root = fs::canonicalize(root)?;
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let file_type = fs::symlink_metadata(&path)?.file_type();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_symlink()
+                && let Ok(resolved) = fs::canonicalize(&path)
+                && !resolved.starts_with(&canonical_root)
+            {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                return Err(TarSmithError::UnsafeSymlink(
+                    relative,
+                    fs::read_link(&path)?,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::elf;
+use crate::tree::AppTree;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Sonames (up to the version) of toolkit and windowing libraries only graphical programs link against
+const GUI_LIBRARIES: &[&str] = &[
+    "libX11.so",
+    "libxcb.so",
+    "libwayland-client.so",
+    "libgtk-",
+    "libgdk-",
+    "libQt5Gui.so",
+    "libQt6Gui.so",
+    "libSDL2-",
+    "libSDL3.so",
+    "libglfw.so",
+    "libfltk.so",
+    "libwx_gtk",
+    "libXm.so",
+];
+
+/// Files next to an executable that mark an Electron or CEF app
+const ELECTRON_MARKERS: &[&str] = &[
+    "resources/app.asar",
+    "resources.pak",
+    "chrome_100_percent.pak",
+    "libcef.so",
+];
+
+/// AWT's X11 toolkit inside a Java runtime bundled with the app, as in JetBrains IDEs
+const JAVA_GUI_MARKERS: &[&str] = &["jbr/lib/libawt_xawt.so", "jre/lib/libawt_xawt.so"];
+
+/// Whether an executable opens windows or runs in a terminal, and why TarSmith thinks so
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interface {
+    Gui(String),
+    Terminal(String),
+    /// Not decided without the extracted files; dry runs treat these as graphical
+    Unknown(String),
+}
+
+impl Interface {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Interface::Terminal(_))
+    }
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interface::Gui(reason) => write!(f, "GUI ({})", reason),
+            Interface::Terminal(reason) => write!(f, "terminal program ({})", reason),
+            Interface::Unknown(reason) => write!(f, "assumed GUI ({})", reason),
+        }
+    }
+}
+
+/// Finds a GUI toolkit among an executable's DT_NEEDED libraries
+fn gui_library(needed: &[String]) -> Option<&String> {
+    needed
+        .iter()
+        .find(|lib| GUI_LIBRARIES.iter().any(|gui| lib.starts_with(gui)))
+}
+
+/// Finds a library next to an executable that links a GUI toolkit, as Firefox's `libxul.so` does
+/// for a `firefox` binary that only loads it at runtime
+fn gui_neighbor(exe: &Path) -> Option<String> {
+    fs::read_dir(exe.parent()?)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.ends_with(".so") || name.contains(".so.")
+        })
+        .find_map(|entry| {
+            let info = elf::dynamic_info(&entry.path())?;
+            let lib = gui_library(&info.needed)?;
+            Some(format!(
+                "{} next to it links against {}",
+                entry.file_name().to_string_lossy(),
+                lib
+            ))
+        })
+}
+
+/// Which marker of a bundled GUI runtime the app ships, if any; `exists` checks a path relative to the app root
+fn gui_runtime(exe_dir: &Path, exists: impl Fn(&Path) -> bool) -> Option<Interface> {
+    if ELECTRON_MARKERS
+        .iter()
+        .any(|marker| exists(&exe_dir.join(marker)))
+    {
+        return Some(Interface::Gui("Electron app".to_string()));
+    }
+    if JAVA_GUI_MARKERS
+        .iter()
+        .any(|marker| exists(Path::new(marker)))
+    {
+        return Some(Interface::Gui("bundled Java GUI runtime".to_string()));
+    }
+    None
+}
+
+/// Classifies an executable of the app: a bundled `.desktop` file launching it
+/// (`bundled_program`) or a GUI library in its DT_NEEDED entries (or in those of a library next
+/// to it) make it graphical, as do Electron and bundled Java GUI runtimes; other native programs
+/// and scripts are terminal programs
+/// Linked libraries cannot be read from an archive listing, so there ELF programs without other
+/// clues are `Unknown`
+pub fn classify(tree: &AppTree, exe: &Path, bundled_program: Option<&str>) -> Interface {
+    let name = exe.file_name().unwrap_or_default().to_string_lossy();
+    if bundled_program == Some(name.as_ref()) {
+        return Interface::Gui("the app ships a desktop entry for it".to_string());
+    }
+    let exe_dir = exe
+        .parent()
+        .and_then(|dir| dir.strip_prefix(tree.root()).ok())
+        .unwrap_or(Path::new(""));
+    let runtime = gui_runtime(exe_dir, |path| tree.exists(path));
+
+    if let AppTree::Disk(_) = tree
+        && let Some(info) = elf::dynamic_info(exe)
+    {
+        if let Some(lib) = gui_library(&info.needed) {
+            return Interface::Gui(format!("links against {}", lib));
+        }
+        if let Some(reason) = gui_neighbor(exe) {
+            return Interface::Gui(reason);
+        }
+        return runtime
+            .unwrap_or_else(|| Interface::Terminal("links no GUI libraries".to_string()));
+    }
+
+    let header = tree.header(exe);
+    if let AppTree::Listing { .. } = tree
+        && header.starts_with(b"\x7fELF")
+    {
+        return runtime.unwrap_or_else(|| {
+            Interface::Unknown("linked libraries are only read during a real install".to_string())
+        });
+    }
+    let reason = if header.starts_with(b"#!") {
+        "script"
+    } else {
+        "not a native program"
+    };
+    runtime.unwrap_or_else(|| Interface::Terminal(reason.to_string()))
+}
@@ -0,0 +1,242 @@
+use crate::TarSmithError;
+use crate::discover::normalize;
+use crate::registry;
+use crate::transaction::Transaction;
+use crate::tree::{self, AppTree};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Generic file names apps use for their own icon
+const GENERIC_NAMES: &[&str] = &["icon", "appicon", "logo"];
+
+/// Fixed-size directories of the hicolor theme; other sizes are filed under the next smaller one
+const THEME_SIZES: &[u32] = &[16, 22, 24, 32, 36, 48, 64, 72, 96, 128, 192, 256, 512];
+
+/// Image formats the icon theme spec allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconFormat {
+    Png,
+    Svg,
+    Xpm,
+}
+
+impl IconFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "png" => Some(IconFormat::Png),
+            "svg" => Some(IconFormat::Svg),
+            "xpm" => Some(IconFormat::Xpm),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            IconFormat::Png => "png",
+            IconFormat::Svg => "svg",
+            IconFormat::Xpm => "xpm",
+        }
+    }
+}
+
+/// An icon found in the app's tree and the hicolor directory it belongs in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    pub source: PathBuf,
+    /// Theme directory name, e.g. `48x48` or `scalable`
+    pub size_dir: String,
+    format: IconFormat,
+}
+
+impl Icon {
+    /// Path of the copy inside `theme_dir`, named so `Icon=<icon_name>` finds it
+    pub fn theme_path(&self, theme_dir: &Path, icon_name: &str) -> PathBuf {
+        theme_dir.join(&self.size_dir).join("apps").join(format!(
+            "{}.{}",
+            icon_name,
+            self.format.extension()
+        ))
+    }
+}
+
+/// A discovered image and the facts it is ranked by
+struct Candidate {
+    path: PathBuf,
+    stem: String,
+    depth: usize,
+    format: IconFormat,
+    header: Vec<u8>,
+}
+
+impl Candidate {
+    /// Ranks how likely this is the app's icon: 0 means it is not one
+    /// - name: 30 if it equals the app name, 20 if one contains the other, 10 for `icon` or `logo`
+    /// - location: -1 per directory level, so shallow files win ties
+    fn score(&self, app_name: &str) -> i32 {
+        let stem = normalize(&self.stem);
+        let app = normalize(app_name);
+        let name_score = if stem.is_empty() || app.is_empty() {
+            0
+        } else if stem == app {
+            30
+        } else if stem.len() >= 3 && (stem.contains(&app) || app.contains(&stem)) {
+            20
+        } else if GENERIC_NAMES.contains(&stem.as_str()) {
+            10
+        } else {
+            0
+        };
+        if name_score == 0 {
+            0
+        } else {
+            name_score - self.depth as i32
+        }
+    }
+
+    /// The hicolor directory for this image, from the pixel size in its header
+    fn size_dir(&self) -> Option<String> {
+        let (width, height) = match self.format {
+            IconFormat::Svg => return Some("scalable".to_string()),
+            IconFormat::Png => png_size(&self.header)?,
+            IconFormat::Xpm => xpm_size(&self.header)?,
+        };
+        if width != height {
+            return None;
+        }
+        THEME_SIZES
+            .iter()
+            .rev()
+            .find(|size| **size <= width)
+            .map(|size| format!("{0}x{0}", size))
+    }
+}
+
+/// Reads width and height from a PNG's IHDR chunk
+fn png_size(header: &[u8]) -> Option<(u32, u32)> {
+    if header.len() < 24 || !header.starts_with(b"\x89PNG\r\n\x1a\n") || &header[12..16] != b"IHDR"
+    {
+        return None;
+    }
+    let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Reads width and height from the first string of an XPM image, `"<width> <height> <colors> <chars>"`
+fn xpm_size(header: &[u8]) -> Option<(u32, u32)> {
+    let text = String::from_utf8_lossy(header);
+    if !text.starts_with("/* XPM */") {
+        return None;
+    }
+    let values = text.split('{').nth(1)?.split('"').nth(1)?;
+    let mut numbers = values.split_whitespace().map(|n| n.parse::<u32>().ok());
+    Some((numbers.next()??, numbers.next()??))
+}
+
+/// Finds the app's icons in its tree: png, svg and xpm files named after the app, or `icon`/`logo`,
+/// anywhere from the root down to `resources/` and `share/icons/`
+/// At most one icon is returned per theme size, the best-named and shallowest one
+pub fn find_icons(tree: &AppTree, app_name: &str) -> Result<Vec<Icon>, TarSmithError> {
+    let candidates = tree
+        .files(&tree::ICONS)?
+        .into_iter()
+        .filter_map(|file| {
+            Some(Candidate {
+                format: IconFormat::from_path(&file.relative)?,
+                stem: file.relative.file_stem()?.to_string_lossy().to_string(),
+                depth: file.depth,
+                header: file.header().into_owned(),
+                path: file.path,
+            })
+        })
+        .collect();
+    Ok(choose(candidates, app_name))
+}
+
+/// Keeps the best candidate for each theme size, ordered by size directory
+fn choose(candidates: Vec<Candidate>, app_name: &str) -> Vec<Icon> {
+    let mut best: Vec<(i32, Icon)> = Vec::new();
+    for candidate in candidates {
+        let score = candidate.score(app_name);
+        if score <= 0 {
+            continue;
+        }
+        let Some(size_dir) = candidate.size_dir() else {
+            continue;
+        };
+        let icon = Icon {
+            source: candidate.path,
+            size_dir,
+            format: candidate.format,
+        };
+
+        match best.iter_mut().find(|(_, b)| b.size_dir == icon.size_dir) {
+            Some(slot) => {
+                if score > slot.0 || (score == slot.0 && icon.source < slot.1.source) {
+                    *slot = (score, icon);
+                }
+            }
+            None => best.push((score, icon)),
+        }
+    }
+
+    let mut icons: Vec<Icon> = best.into_iter().map(|(_, icon)| icon).collect();
+    icons.sort_by(|a, b| a.size_dir.cmp(&b.size_dir));
+    icons
+}
+
+/// Returns the hicolor theme directory: ~/.local/share/icons/hicolor for user-level,
+/// /usr/local/share/icons/hicolor for system-wide, leaving /usr/share to the distribution
+pub fn theme_dir(is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    if is_user_level {
+        Ok(dirs::home_dir()
+            .ok_or(TarSmithError::HomeDirectoryNotFound)?
+            .join(".local/share/icons/hicolor"))
+    } else {
+        Ok(Path::new("/usr/local/share/icons/hicolor").to_path_buf())
+    }
+}
+
+/// Whether an icon file exists that no TarSmith install record owns, e.g. one from a package;
+/// such files are never replaced
+pub fn is_foreign(path: &Path, is_user_level: bool) -> bool {
+    fs::symlink_metadata(path).is_ok()
+        && !registry::load_records(is_user_level)
+            .unwrap_or_default()
+            .iter()
+            .any(|record| record.icons.iter().any(|icon| icon == path))
+}
+
+/// Copies icons into the hicolor theme as `<icon_name>.<ext>` and returns the copies' paths;
+/// sizes already provided by a file TarSmith does not own are skipped
+pub fn install_icons(
+    icons: &[Icon],
+    icon_name: &str,
+    is_user_level: bool,
+    transaction: &mut Transaction,
+) -> Result<Vec<PathBuf>, TarSmithError> {
+    let theme_dir = theme_dir(is_user_level)?;
+    let mut installed = Vec::new();
+    for icon in icons {
+        let target = icon.theme_path(&theme_dir, icon_name);
+        if is_foreign(&target, is_user_level) {
+            println!(
+                "    Kept existing icon: {} (not installed by TarSmith)",
+                target.display()
+            );
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        transaction.prepare_write(&target)?;
+        fs::copy(&icon.source, &target)?;
+        println!(
+            "    Installed icon: {} (from {})",
+            target.display(),
+            icon.source.display()
+        );
+        installed.push(target);
+    }
+    Ok(installed)
+}
@@ -0,0 +1,131 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Second line of every generated wrapper, used to recognise TarSmith launchers
+pub const WRAPPER_MARKER: &str = "# Launcher generated by TarSmith";
+
+/// How executables are put on PATH
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// A symlink to the executable
+    #[default]
+    Symlink,
+    /// A small shell script that sets up the environment and `exec`s the executable
+    Wrapper,
+}
+
+/// Environment and working directory a wrapper sets before `exec`ing the executable
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WrapperConfig {
+    /// `KEY=VALUE` pairs; values may refer to `$APP_DIR` and other variables
+    #[serde(default)]
+    pub env: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+/// Parses a `KEY=VALUE` environment assignment, as given to `--wrapper-env`
+pub fn parse_env(assignment: &str) -> Result<String, String> {
+    let (key, _) = assignment
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got {}", assignment))?;
+    let mut chars = key.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(assignment.to_string())
+    } else {
+        Err(format!("invalid environment variable name: {}", key))
+    }
+}
+
+/// Quotes a string for a POSIX shell without allowing any expansion
+fn single_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Quotes a string for a POSIX shell, still expanding `$VARIABLES` at launch
+fn double_quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        if matches!(c, '"' | '\\' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders a launcher for `exec_file` of an app whose active version is at `app_root`
+pub fn wrapper_script(
+    app_name: &str,
+    app_root: &Path,
+    exec_file: &Path,
+    config: &WrapperConfig,
+) -> String {
+    let mut script = format!(
+        "#!/bin/sh\n{} for {}\nAPP_DIR={}\n",
+        WRAPPER_MARKER,
+        app_name,
+        single_quote(&app_root.to_string_lossy())
+    );
+    for assignment in &config.env {
+        if let Some((key, value)) = assignment.split_once('=') {
+            script.push_str(&format!("export {}={}\n", key, double_quote(value)));
+        }
+    }
+    if let Some(cwd) = &config.cwd {
+        script.push_str(&format!("cd {} || exit 1\n", double_quote(cwd)));
+    }
+    script.push_str(&format!(
+        "exec {} \"$@\"\n",
+        single_quote(&exec_file.to_string_lossy())
+    ));
+    script
+}
+
+/// Whether a file is a launcher generated by TarSmith, judged from its first lines only
+pub fn is_wrapper(path: &Path) -> bool {
+    if path.is_symlink() {
+        return false;
+    }
+    let mut header = Vec::new();
+    if File::open(path)
+        .and_then(|file| file.take(256).read_to_end(&mut header))
+        .is_err()
+    {
+        return false;
+    }
+    String::from_utf8_lossy(&header)
+        .lines()
+        .nth(1)
+        .is_some_and(|line| line.starts_with(WRAPPER_MARKER))
+}
+
+/// Returns what a PATH entry launches: a symlink's target, or the executable a TarSmith wrapper `exec`s
+pub fn target(path: &Path) -> Option<PathBuf> {
+    if let Ok(target) = fs::read_link(path) {
+        return Some(target);
+    }
+    if !is_wrapper(path) {
+        return None;
+    }
+    let contents = fs::read_to_string(path).ok()?;
+    let quoted = contents
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("exec "))?
+        .strip_suffix(" \"$@\"")?;
+    let unquoted = quoted
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?
+        .replace(r"'\''", "'");
+    Some(PathBuf::from(unquoted))
+}
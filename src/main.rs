@@ -1,18 +1,61 @@
-use clap::Parser;
+mod archive_name;
+mod caches;
+mod checksum;
+mod conflicts;
+mod deps;
+mod desktop;
+mod discover;
+mod elf;
+mod extract;
+mod gui;
+mod icons;
+mod launcher;
+mod plan;
+mod registry;
+mod selection;
+mod signature;
+mod transaction;
+mod tree;
+mod uninstall;
+mod upgrade;
+mod versions;
+
+use clap::{Parser, Subcommand};
+use launcher::{LinkMode, WrapperConfig};
+use registry::InstallRecord;
+use selection::{DesktopChoice, PathChoice};
+use transaction::Transaction;
+use tree::AppTree;
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+/// Comment line written above the PATH export appended to shell config files
+const PATH_EXPORT_MARKER: &str = "# Added by TarSmith installer";
+
 #[derive(Error, Debug)]
 pub enum TarSmithError {
     #[error("Archive not found: {0}")]
     ArchiveNotFound(PathBuf),
 
-    #[error("Extraction failed")]
-    ExtractionFailed,
+    #[error("Extraction failed: {0}")]
+    ExtractionFailed(String),
+
+    #[error("Failed to extract entry {0}: {1}")]
+    EntryExtractionFailed(PathBuf, String),
+
+    #[error("Unsupported archive format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Archive entry escapes the install directory: {0}")]
+    UnsafeArchivePath(PathBuf),
+
+    #[error("Archive symlink {0} points outside the install directory: {1}")]
+    UnsafeSymlink(PathBuf, PathBuf),
 
     #[error("Invalid selection: {0}")]
     InvalidSelection(String),
@@ -32,7 +75,10 @@ pub enum TarSmithError {
     #[error("Cannot get folder name from path")]
     CannotGetFolderName,
 
-    #[error("No executable found in bin/ folder")]
+    #[error("Cannot derive an app name from {0:?}")]
+    InvalidAppName(String),
+
+    #[error("No executable found in the archive")]
     NoExecutableFound,
 
     #[error("Cannot determine home directory")]
@@ -41,17 +87,71 @@ pub enum TarSmithError {
     #[error("Invalid number format: {0}")]
     InvalidNumberFormat(String),
 
+    #[error("Invalid install record {0}: {1}")]
+    InvalidRecord(PathBuf, String),
+
+    #[error("No install record found for: {0}")]
+    AppNotInstalled(String),
+
+    #[error("Version {version} of {app} is not installed (installed: {available})")]
+    VersionNotInstalled {
+        app: String,
+        version: String,
+        available: String,
+    },
+
+    #[error("Invalid SHA-256 checksum: {0}")]
+    InvalidChecksum(String),
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("No checksum for {1} in {0}")]
+    ChecksumNotFound(PathBuf, String),
+
+    #[error("Signature verification failed: {0}")]
+    SignatureVerificationFailed(String),
+
+    #[error("Archive {archive} looks like {found}, not {app}")]
+    ArchiveAppMismatch {
+        app: String,
+        found: String,
+        archive: PathBuf,
+    },
+
+    #[error("Version {new} is not newer than installed {installed} (use --allow-downgrade)")]
+    NotNewerVersion { installed: String, new: String },
+
+    #[error("Executables are built for {found}, but this machine is {host} (use --ignore-arch to install anyway)")]
+    ArchitectureMismatch { found: String, host: String },
+
+    #[error("No executable matches {pattern} (found: {available})")]
+    NoMatchingExecutable { pattern: String, available: String },
+
+    #[error("Missing shared libraries: {0}")]
+    MissingLibraries(String),
+
+    #[error("{0} exists but was not installed by TarSmith (use --force to replace it)")]
+    ForeignAppDirectory(PathBuf),
+
+    #[error("Invalid desktop entry: {0}")]
+    InvalidDesktopEntry(String),
+
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "tarsmith")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(about = "A simple, interactive installer for tar archives", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    #[arg(value_name = "FILE")]
-    archive: PathBuf,
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[arg(value_name = "FILE", required = true)]
+    archive: Option<PathBuf>,
 
     #[arg(short = 's', long = "system", conflicts_with = "user")]
     system: bool,
@@ -64,21 +164,191 @@ struct Args {
 
     #[arg(short = 'p', long = "no-path")]
     no_path: bool,
+
+    #[command(flatten)]
+    checks: Checks,
+
+    #[arg(long = "desktop-exec", value_name = "NAME|GLOB", conflicts_with = "no_desktop")]
+    desktop_exec: Option<String>,
+
+    #[arg(long = "path-exec", value_name = "NAME|GLOB", conflicts_with = "no_path")]
+    path_exec: Vec<String>,
+
+    #[arg(long = "force", conflicts_with_all = ["link_prefix", "link_suffix"])]
+    force: bool,
+
+    #[arg(long = "link-prefix", value_name = "PREFIX", allow_hyphen_values = true)]
+    link_prefix: Option<String>,
+
+    #[arg(long = "link-suffix", value_name = "SUFFIX", allow_hyphen_values = true)]
+    link_suffix: Option<String>,
+
+    #[arg(long = "link-mode", value_enum, default_value_t = LinkMode::Symlink)]
+    link_mode: LinkMode,
+
+    #[arg(long = "wrapper-env", value_name = "KEY=VALUE", value_parser = launcher::parse_env)]
+    wrapper_env: Vec<String>,
+
+    #[arg(long = "wrapper-cwd", value_name = "DIR")]
+    wrapper_cwd: Option<String>,
+
+    #[arg(long = "dry-run", visible_alias = "plan")]
+    dry_run: bool,
+
+    #[arg(long = "json", requires = "dry_run")]
+    json: bool,
+}
+
+/// Checks of the archive and its executables, shared by installs and upgrades
+#[derive(clap::Args, Debug, Clone)]
+struct Checks {
+    #[arg(long = "sha256", value_name = "HEX", conflicts_with = "checksum_file")]
+    sha256: Option<String>,
+
+    #[arg(long = "checksum-file", value_name = "SHA256SUMS")]
+    checksum_file: Option<PathBuf>,
+
+    #[arg(long = "signature", value_name = "FILE")]
+    signature: Option<PathBuf>,
+
+    #[arg(long = "pubkey", value_name = "FILE", requires = "signature")]
+    pubkey: Option<PathBuf>,
+
+    #[arg(long = "ignore-arch")]
+    ignore_arch: bool,
+
+    #[arg(long = "strict-deps")]
+    strict_deps: bool,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Commands {
+    /// List apps installed by TarSmith
+    List,
+
+    /// Remove an installed app and everything its install created
+    Uninstall {
+        #[arg(value_name = "APP")]
+        app: String,
+
+        #[arg(short = 's', long = "system")]
+        system: bool,
+    },
+
+    /// Install a newer archive of an app, keeping its desktop and PATH choices
+    Upgrade {
+        #[arg(value_name = "APP")]
+        app: String,
+
+        #[arg(value_name = "FILE")]
+        archive: PathBuf,
+
+        #[arg(short = 's', long = "system")]
+        system: bool,
+
+        #[arg(long = "allow-downgrade")]
+        allow_downgrade: bool,
+
+        #[command(flatten)]
+        checks: Checks,
+    },
+
+    /// Point an app's `current` link at another installed version
+    Switch {
+        #[arg(value_name = "APP")]
+        app: String,
+
+        #[arg(value_name = "VERSION")]
+        version: String,
+
+        #[arg(short = 's', long = "system")]
+        system: bool,
+    },
 }
 
 fn main() -> Result<(), TarSmithError> {
     let args = Args::parse();
+    if args.link_mode != LinkMode::Wrapper
+        && (!args.wrapper_env.is_empty() || args.wrapper_cwd.is_some())
+    {
+        return Err(TarSmithError::InvalidSelection(
+            "--wrapper-env and --wrapper-cwd need --link-mode wrapper".to_string(),
+        ));
+    }
 
-    let archive_path = &args.archive;
-    let install_type = if args.system {
+    match &args.command {
+        Some(Commands::List) => registry::list_installs(),
+        Some(Commands::Uninstall { app, system }) => uninstall::uninstall(app, !system),
+        Some(Commands::Switch {
+            app,
+            version,
+            system,
+        }) => versions::switch(app, version, !system),
+        Some(Commands::Upgrade {
+            app,
+            archive,
+            system,
+            allow_downgrade,
+            checks,
+        }) => {
+            let upgrade_args = Args {
+                checks: checks.clone(),
+                ..args.clone()
+            };
+            upgrade::upgrade(app, archive, !system, *allow_downgrade, &upgrade_args)
+        }
+        None => {
+            let archive = args
+                .archive
+                .as_deref()
+                .ok_or(TarSmithError::CannotFindArchiveName)?;
+            if args.dry_run {
+                plan::dry_run(archive, &args)
+            } else {
+                install(archive, &args, None)
+            }
+        }
+    }
+}
+
+/// Runs an install as a transaction: on any error every change made so far is undone
+/// and a previously installed version is restored
+/// With a `previous` record the install is an upgrade and reuses its choices instead of prompting
+fn install(
+    archive_path: &Path,
+    args: &Args,
+    previous: Option<&InstallRecord>,
+) -> Result<(), TarSmithError> {
+    let mut transaction = Transaction::new();
+    match run_install(archive_path, args, previous, &mut transaction) {
+        Ok(()) => {
+            transaction.commit();
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Installation failed: {}", e);
+            eprintln!("Rolling back changes...");
+            transaction.rollback();
+            Err(e)
+        }
+    }
+}
+
+fn run_install(
+    archive_path: &Path,
+    args: &Args,
+    previous: Option<&InstallRecord>,
+    transaction: &mut Transaction,
+) -> Result<(), TarSmithError> {
+    let install_type = if let Some(previous) = previous {
+        Some(previous.is_user_level)
+    } else if args.system {
         Some(false)
     } else if args.user {
         Some(true)
     } else {
         None
     };
-    let no_desktop = args.no_desktop;
-    let no_path = args.no_path;
 
     println!("=== TarSmith Installer ===");
     println!("Input file: {}", archive_path.display());
@@ -89,6 +359,14 @@ fn main() -> Result<(), TarSmithError> {
     }
     println!("[1] File exists ✔");
 
+    let (sha256, sha256_verified, signature_format) = verify_archive(archive_path, args)?;
+    if sha256_verified {
+        println!("[1] SHA-256 verified: {} ✔", sha256);
+    }
+    if let Some(format) = signature_format {
+        println!("[1] {} signature verified ✔", format);
+    }
+
     let (install_dir, is_user_level) = if let Some(user_level) = install_type {
         if !user_level {
             if !check_sudo_permissions() {
@@ -100,11 +378,9 @@ fn main() -> Result<(), TarSmithError> {
                 eprintln!("Or use --user for user-level installation which doesn't require sudo.");
                 std::process::exit(1);
             }
-            (Path::new("/opt").to_path_buf(), false)
+            (install_root(false)?, false)
         } else {
-            (dirs::home_dir()
-                .ok_or(TarSmithError::HomeDirectoryNotFound)?
-                .join(".local/tarsmith"), true)
+            (install_root(true)?, true)
         }
     } else {
         println!("Choose installation type:");
@@ -126,11 +402,9 @@ fn main() -> Result<(), TarSmithError> {
                 );
                 std::process::exit(1);
             }
-            (Path::new("/opt").to_path_buf(), false)
+            (install_root(false)?, false)
         } else {
-            (dirs::home_dir()
-                .ok_or(TarSmithError::HomeDirectoryNotFound)?
-                .join(".local/tarsmith"), true)
+            (install_root(true)?, true)
         }
     };
 
@@ -151,52 +425,25 @@ fn main() -> Result<(), TarSmithError> {
     }
     fs::create_dir_all(&temp_dir)?;
 
-    let tar_flags = archive_path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| {
-            if ext == "gz" || ext == "tgz" {
-                "-xzf"
-            } else if ext == "xz" || ext == "txz" {
-                "-xJf"
-            } else if ext == "bz2" {
-                "-xjf"
-            } else if ext == "zst" {
-                "--zstd -xf"
-            } else {
-                "-xf"
-            }
-        })
-        .unwrap_or("-xf");
-
-    let mut cmd = Command::new("tar");
-
-    if tar_flags.contains("zstd") {
-        let archive_str = archive_path
-            .to_str()
-            .ok_or_else(|| TarSmithError::InvalidSelection("Invalid archive path encoding".to_string()))?;
-        cmd.args(["--zstd", "-xf", archive_str]);
-    } else {
-        cmd.arg(tar_flags);
-        cmd.arg(archive_path);
-    }
-
-    cmd.arg("-C").arg(&temp_dir);
-
-    let status = cmd.status()?;
-
-    if !status.success() {
+    extract::extract_archive(archive_path, &temp_dir).inspect_err(|_| {
         fs::remove_dir_all(&temp_dir).ok();
-        return Err(TarSmithError::ExtractionFailed);
-    }
+    })?;
     println!("[3] Extraction complete ✔");
 
     println!("[4] Detecting installation folder...");
 
-    let extracted_path = analyze_and_move_extraction(&temp_dir, &install_dir, archive_path)
-        .map_err(|e| {
+    let (extracted_path, app_name, version) =
+        analyze_and_move_extraction(
+            &temp_dir,
+            &install_dir,
+            archive_path,
+            previous,
+            is_user_level,
+            args.force,
+            transaction,
+        )
+        .inspect_err(|_| {
             fs::remove_dir_all(&temp_dir).ok();
-            e
         })?;
 
     fs::remove_dir_all(&temp_dir).ok();
@@ -204,189 +451,363 @@ fn main() -> Result<(), TarSmithError> {
         "[4] Detected installation directory: {} ✔",
         extracted_path.display()
     );
-
-    let app_name = infer_app_name(&extracted_path)?;
     println!("[4] Inferred app name: {} ✔", app_name);
+    println!("[4] Installed version: {} (now current) ✔", version);
 
-    let exec_path = extracted_path.join("bin");
-    let executables = if exec_path.exists() && exec_path.is_dir() {
-        find_executables_in_bin(&exec_path)?
-    } else {
-        find_executables_in_bin(&extracted_path)?
-    };
+    let parsed_name = archive_name::parse(
+        &archive_path
+            .file_name()
+            .ok_or(TarSmithError::CannotFindArchiveName)?
+            .to_string_lossy(),
+    );
+    let platform: Vec<&str> = [&parsed_name.os, &parsed_name.arch, &parsed_name.libc]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    if !platform.is_empty() {
+        println!("[4] Built for: {} ✔", platform.join(" "));
+    }
 
-    let desktop_exec = if no_desktop {
-        None
-    } else if install_type.is_some() {
-        if executables.is_empty() {
-            None
-        } else {
-            println!(
-                "[5] Using first executable for desktop entry: {}",
-                executables[0]
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-            );
-            Some(executables[0].clone())
+    let tree = AppTree::Disk(&extracted_path);
+    let selection = selection::select(
+        &tree,
+        &app_name,
+        args,
+        previous,
+        install_type.is_none(),
+    )?;
+    for warning in &selection.arch.warnings {
+        println!("    Warning: {}", warning);
+    }
+    if let Some(arch) = &selection.arch.matched {
+        println!("[4] Architecture: {} matches this machine ✔", arch);
+    }
+    let executables = &selection.executables;
+    let interfaces = &selection.interfaces;
+
+    let desktop_exec = match &selection.desktop {
+        DesktopChoice::Kept(name, found) => {
+            match found {
+                Some(_) => println!("[5] Keeping desktop entry executable: {}", name),
+                None => println!(
+                    "[5] Desktop entry executable {} is gone in this version, skipping",
+                    name
+                ),
+            }
+            found.clone()
         }
-    } else {
-        println!("[5] Select executable for desktop entry (GUI launch):");
-        if executables.len() == 1 {
+        DesktopChoice::Skipped => None,
+        DesktopChoice::Matched(pattern, exe) => {
             println!(
-                "  Only one executable found, using: {}",
-                executables[0]
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
+                "[5] Using executable matching {} for desktop entry: {}",
+                pattern,
+                exe_name(exe)
             );
-            Some(executables[0].clone())
-        } else {
-            println!("  Executables found:");
-            for (i, exe) in executables.iter().enumerate() {
+            Some(exe.clone())
+        }
+        DesktopChoice::Gui(exe) => {
+            if let Some(interface) = selection.interface(exe) {
                 println!(
-                    "    {}) {}",
-                    i + 1,
-                    exe.file_name().unwrap_or_default().to_string_lossy()
+                    "[5] Using top-ranked GUI executable for desktop entry (default): {}, {}",
+                    exe_name(exe),
+                    interface
                 );
             }
-            println!("    0) Skip desktop entry");
-            print!(
-                "  Select executable (0-{}) [default: 0]: ",
-                executables.len()
-            );
-            io::stdout().flush()?;
+            Some(exe.clone())
+        }
+        DesktopChoice::NoGui => {
+            if let (Some(exe), Some(interface)) = (executables.first(), interfaces.first()) {
+                println!(
+                    "[5] No GUI executable found ({} is a {}), skipping desktop entry (use --desktop-exec to create one)",
+                    exe_name(exe),
+                    interface
+                );
+            }
+            None
+        }
+        DesktopChoice::Ask => {
+            println!("[5] Select executable for desktop entry (GUI launch):");
+            if executables.len() == 1 && interfaces[0].is_terminal() {
+                println!(
+                    "  Only one executable found: {}, a {}",
+                    elf::describe(&executables[0]),
+                    interfaces[0]
+                );
+                print!("  Create a desktop entry that opens it in a terminal? (y/N): ");
+                io::stdout().flush()?;
 
-            let mut selection = String::new();
-            io::stdin().read_line(&mut selection)?;
-            let selection = selection.trim();
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                let response = response.trim().to_lowercase();
 
-            if selection.is_empty() || selection == "0" {
-                None
+                if response == "y" || response == "yes" {
+                    Some(executables[0].clone())
+                } else {
+                    None
+                }
+            } else if executables.len() == 1 {
+                println!(
+                    "  Only one executable found, using: {}",
+                    elf::describe(&executables[0])
+                );
+                Some(executables[0].clone())
             } else {
-                let selection: usize = selection.parse().map_err(|_| TarSmithError::InvalidSelection("Invalid number format".to_string()))?;
-                if selection < 1 || selection > executables.len() {
-                    return Err(TarSmithError::InvalidSelection(format!("Selection {} is out of range", selection)));
+                println!("  Executables found:");
+                for (i, (exe, interface)) in executables.iter().zip(interfaces).enumerate() {
+                    println!(
+                        "    {}) {}{}{}",
+                        i + 1,
+                        elf::describe(exe),
+                        if interface.is_terminal() { " [terminal]" } else { "" },
+                        if i == 0 { " (top-ranked)" } else { "" }
+                    );
+                }
+                println!("    0) Skip desktop entry");
+                print!(
+                    "  Select executable (0-{}) [default: 0]: ",
+                    executables.len()
+                );
+                io::stdout().flush()?;
+
+                let mut choice = String::new();
+                io::stdin().read_line(&mut choice)?;
+                let choice = choice.trim();
+
+                if choice.is_empty() || choice == "0" {
+                    None
+                } else {
+                    let choice: usize = choice.parse().map_err(|_| TarSmithError::InvalidSelection("Invalid number format".to_string()))?;
+                    if choice < 1 || choice > executables.len() {
+                        return Err(TarSmithError::InvalidSelection(format!("Selection {} is out of range", choice)));
+                    }
+                    Some(executables[choice - 1].clone())
                 }
-                Some(executables[selection - 1].clone())
             }
         }
     };
 
-    if let Some(exec_file) = &desktop_exec {
+    let terminal = desktop_exec
+        .as_ref()
+        .and_then(|exec| selection.interface(exec))
+        .is_some_and(gui::Interface::is_terminal);
+    if let Some(exec) = &desktop_exec
+        && terminal
+    {
+        println!(
+            "    {} is a terminal program, the desktop entry will open it in a terminal",
+            exe_name(exec)
+        );
+    }
+
+    let (desktop_file, icon_files) = if let Some(exec_file) = &desktop_exec {
         println!("[6] Creating desktop entry...");
-        let desktop_filename = format!("{}.desktop", app_name);
-        let desktop_path = if is_user_level {
-            dirs::home_dir()
-                .ok_or(TarSmithError::HomeDirectoryNotFound)?
-                .join(".local/share/applications")
-                .join(&desktop_filename)
-        } else {
-            Path::new("/usr/share/applications").join(&desktop_filename)
-        };
+        let desktop_path = desktop_file_path(&app_name, is_user_level)?;
 
         if let Some(parent) = desktop_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let icon_path = find_icon(&extracted_path)
-            .unwrap_or_else(|| extracted_path.join("bin").join("icon.png"));
-
-        let desktop_contents = format!(
-            "[Desktop Entry]
-Version=1.0
-Type=Application
-Name={}
-Exec={}
-Icon={}
-Terminal=false
-Categories=Utility;
-",
-            app_name,
-            exec_file.display(),
-            icon_path.display()
-        );
+        let found_icons = icons::find_icons(&tree, &app_name)?;
+        let icon_files = icons::install_icons(&found_icons, &app_name, is_user_level, transaction)?;
+        if found_icons.is_empty() {
+            println!("    No icon found, the entry will use the default one");
+        }
+        let bundled = desktop::find_bundled(&tree, &app_name, &exe_name(exec_file))?;
+        let (desktop_contents, source, dropped) = desktop_entry_contents(
+            &app_name,
+            exec_file,
+            executables,
+            (!found_icons.is_empty()).then_some(app_name.as_str()),
+            terminal,
+            bundled.as_ref(),
+        )?;
+        if let Some(source) = source {
+            println!("    Desktop entry {}", source);
+        }
+        for warning in dropped {
+            println!("    Warning: {}", warning);
+        }
 
+        transaction.prepare_write(&desktop_path)?;
         fs::write(&desktop_path, desktop_contents)?;
         println!("[6] Desktop entry created at: {} ✔", desktop_path.display());
+        (Some(desktop_path), icon_files)
     } else {
         println!("[6] Skipped desktop entry creation ✔");
-    }
-
-    let selected_for_path = if no_path {
-        Vec::new()
-    } else if install_type.is_some() {
-        executables.clone()
-    } else {
-        println!("[7] Select executables to add to PATH (for terminal use):");
-        if executables.len() == 1 {
-            println!(
-                "  Only one executable found: {}",
-                executables[0]
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-            );
-            print!("  Add to PATH? (Y/n): ");
-            io::stdout().flush()?;
-
-            let mut response = String::new();
-            io::stdin().read_line(&mut response)?;
-            let response = response.trim().to_lowercase();
+        (None, Vec::new())
+    };
 
-            if response == "n" || response == "no" {
-                Vec::new()
-            } else {
-                vec![executables[0].clone()]
-            }
-        } else {
-            println!("  Executables found:");
-            for (i, exe) in executables.iter().enumerate() {
+    let selected_for_path = match &selection.path {
+        PathChoice::Kept(execs) => {
+            println!("[7] Keeping previous PATH selection...");
+            execs.clone()
+        }
+        PathChoice::Skipped => Vec::new(),
+        PathChoice::Matched(execs) => {
+            println!("[7] Adding matching executables to PATH...");
+            execs.clone()
+        }
+        PathChoice::All(execs) => execs.clone(),
+        PathChoice::Ask => {
+            println!("[7] Select executables to add to PATH (for terminal use):");
+            if executables.len() == 1 {
                 println!(
-                    "    {}) {}",
-                    i + 1,
-                    exe.file_name().unwrap_or_default().to_string_lossy()
+                    "  Only one executable found: {}",
+                    elf::describe(&executables[0])
                 );
-            }
-            print!(
-                "  Enter numbers separated by spaces (e.g., 1 2 3) or 'all' for all [default: all]: "
-            );
-            io::stdout().flush()?;
+                print!("  Add to PATH? (Y/n): ");
+                io::stdout().flush()?;
 
-            let mut selection = String::new();
-            io::stdin().read_line(&mut selection)?;
-            let selection = selection.trim().to_lowercase();
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                let response = response.trim().to_lowercase();
 
-            if selection.is_empty() || selection == "all" {
-                executables.clone()
+                if response == "n" || response == "no" {
+                    Vec::new()
+                } else {
+                    vec![executables[0].clone()]
+                }
             } else {
-                let indices: Vec<usize> = selection
-                    .split_whitespace()
-                    .map(|s| {
-                        s.parse::<usize>()
-                            .map_err(|_| TarSmithError::InvalidNumberFormat(s.to_string()))
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+                println!("  Executables found:");
+                for (i, exe) in executables.iter().enumerate() {
+                    println!("    {}) {}", i + 1, elf::describe(exe));
+                }
+                print!(
+                    "  Enter numbers separated by spaces (e.g., 1 2 3) or 'all' for all [default: all]: "
+                );
+                io::stdout().flush()?;
 
-                let mut selected = Vec::new();
-                for idx in indices {
-                    if idx < 1 || idx > executables.len() {
-                        return Err(TarSmithError::InvalidSelection(format!("Selection {} is out of range", idx)));
+                let mut choice = String::new();
+                io::stdin().read_line(&mut choice)?;
+                let choice = choice.trim().to_lowercase();
+
+                if choice.is_empty() || choice == "all" {
+                    executables.clone()
+                } else {
+                    let indices: Vec<usize> = choice
+                        .split_whitespace()
+                        .map(|s| {
+                            s.parse::<usize>()
+                                .map_err(|_| TarSmithError::InvalidNumberFormat(s.to_string()))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    let mut selected = Vec::new();
+                    for idx in indices {
+                        if idx < 1 || idx > executables.len() {
+                            return Err(TarSmithError::InvalidSelection(format!("Selection {} is out of range", idx)));
+                        }
+                        selected.push(executables[idx - 1].clone());
                     }
-                    selected.push(executables[idx - 1].clone());
+                    selected
                 }
-                selected
             }
         }
     };
 
-    if selected_for_path.is_empty() {
+    let app_dir = extracted_path
+        .parent()
+        .ok_or(TarSmithError::CannotGetDirectoryName)?
+        .to_path_buf();
+    let linked = if selected_for_path.is_empty() {
         println!("[7] Skipped adding to PATH ✔");
+        Vec::new()
     } else {
-        if install_type.is_some() {
+        if let PathChoice::All(_) = selection.path {
             println!("[7] Adding all executables to PATH...");
         }
-        create_path_symlinks(&selected_for_path, is_user_level)?;
+        create_path_symlinks(
+            &selected_for_path,
+            &app_name,
+            &app_dir,
+            is_user_level,
+            previous,
+            args,
+            transaction,
+        )?
+    };
+    let symlinks: Vec<PathBuf> = linked.iter().map(|(_, link)| link.clone()).collect();
+    let (link_mode, wrapper) = link_settings(previous, args);
+
+    let shell_rc = if is_user_level && !symlinks.is_empty() {
+        ensure_local_bin_in_path(transaction)?
+    } else {
+        None
+    };
+
+    let mut chosen: Vec<PathBuf> = desktop_exec.iter().cloned().collect();
+    chosen.extend(
+        selected_for_path
+            .iter()
+            .filter(|e| desktop_exec.as_ref() != Some(*e))
+            .cloned(),
+    );
+    if chosen.is_empty() {
+        chosen = executables.clone();
+    }
+    deps::check_shared_libraries(&chosen, &extracted_path, args.checks.strict_deps)?;
+
+    // A plain install over an installed app replaces its record too, so what the old record
+    // tracks and this install no longer creates is retired just as on upgrades
+    let existing = match previous {
+        Some(_) => None,
+        None => registry::load_record(&app_name, is_user_level).ok(),
+    };
+    let replaced = previous.or(existing.as_ref());
+
+    let refresh_applications = desktop_file.is_some()
+        || replaced.is_some_and(|p| p.desktop_file.is_some());
+    let refresh_icons = !icon_files.is_empty() || replaced.is_some_and(|p| !p.icons.is_empty());
+
+    let executable_names: Vec<String> = executables.iter().map(|e| exe_name(e)).collect();
+    if let Some(replaced) = replaced {
+        upgrade::retire_previous(
+            replaced,
+            &symlinks,
+            desktop_file.as_deref(),
+            &icon_files,
+            transaction,
+        )?;
+    }
+    if let Some(previous) = previous {
+        upgrade::report_executable_changes(&previous.executables, &executable_names);
+    }
+
+    let record = InstallRecord {
+        app_name: app_name.clone(),
+        source_archive: fs::canonicalize(archive_path)?,
+        sha256,
+        sha256_verified,
+        install_dir: app_dir,
+        version,
+        is_user_level,
+        path_execs: linked.iter().map(|(exe, _)| exe_name(exe)).collect(),
+        link_mode,
+        wrapper,
+        symlinks,
+        desktop_file,
+        icons: icon_files,
+        desktop_exec: desktop_exec.as_deref().map(exe_name),
+        executables: executable_names,
+        shell_rc: shell_rc.or_else(|| replaced.and_then(|r| r.shell_rc.clone())),
+        installed_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    transaction.prepare_write(&registry::record_path(&app_name, is_user_level)?)?;
+    let record_path = registry::save_record(&record)?;
+    println!("[9] Install recorded at: {} ✔", record_path.display());
+
+    if refresh_applications || refresh_icons {
+        println!("[10] Refreshing launcher caches...");
+        let desktop_path = desktop_file_path(&app_name, is_user_level)?;
+        let theme_dir = icons::theme_dir(is_user_level)?;
+        caches::refresh(
+            desktop_path.parent().filter(|_| refresh_applications),
+            refresh_icons.then_some(theme_dir.as_path()),
+        );
     }
 
     println!(
@@ -425,47 +846,23 @@ fn check_sudo_permissions() -> bool {
     }
 }
 
-/// Extracts a clean directory name from archive stem, removing version/platform suffixes
-fn extract_dir_name_from_stem(stem: &str) -> String {
-    stem.split(&['-', '_'][..])
-        .take_while(|p| {
-            !p.chars()
-                .next()
-                .map(|c| c.is_ascii_digit())
-                .unwrap_or(false)
-        })
-        .collect::<Vec<_>>()
-        .join("-")
-}
-
-/// Removes existing target path if it exists (handles both files and directories)
-fn remove_existing_target(target_path: &Path) -> Result<(), TarSmithError> {
-    if target_path.exists() {
-        match fs::metadata(target_path) {
-            Ok(metadata) => {
-                if metadata.is_dir() {
-                    fs::remove_dir_all(target_path)?;
-                } else {
-                    fs::remove_file(target_path)?;
-                }
-            }
-            Err(_) => {
-                fs::remove_file(target_path).ok();
-                fs::remove_dir_all(target_path).ok();
-            }
-        }
-    }
-    Ok(())
-}
-
-/// Analyzes the temporary extraction and moves it to the final location
+/// Analyzes the temporary extraction and swaps it into a versioned app directory
 /// Handles both cases: single directory extracted OR files extracted directly
+/// A previous install at the target is kept as a backup until the transaction commits; an upgrade
+/// keeps the recorded app name even when the archive spells it differently, and `force` replaces
+/// an app directory TarSmith did not create
+/// Returns the app's `current` path, its name and the installed version
 fn analyze_and_move_extraction(
     temp_dir: &Path,
     install_dir: &Path,
     archive: &Path,
-) -> Result<PathBuf, TarSmithError> {
+    previous: Option<&InstallRecord>,
+    is_user_level: bool,
+    force: bool,
+    transaction: &mut Transaction,
+) -> Result<(PathBuf, String, String), TarSmithError> {
     let entries: Vec<_> = fs::read_dir(temp_dir)?.collect::<Result<_, _>>()?;
+    let known_name = previous.map(|record| record.app_name.as_str());
 
     if entries.is_empty() {
         return Err(TarSmithError::ArchiveEmpty);
@@ -488,7 +885,7 @@ fn analyze_and_move_extraction(
         }
     }
 
-    let final_path = if dirs.len() == 1 && files.is_empty() {
+    if dirs.len() == 1 && files.is_empty() {
         let extracted_dir = &dirs[0];
         let dir_name = extracted_dir
             .file_name()
@@ -496,212 +893,386 @@ fn analyze_and_move_extraction(
             .to_string_lossy()
             .to_string();
         let target_path = install_dir.join(&dir_name);
-        remove_existing_target(&target_path)?;
-        fs::rename(extracted_dir, &target_path)?;
-        target_path
-    } else if dirs.is_empty() && !files.is_empty() {
-        let stem = archive
-            .file_stem()
-            .ok_or(TarSmithError::CannotFindArchiveName)?
-            .to_string_lossy()
-            .replace(".tar", "");
-
-        let dir_name = extract_dir_name_from_stem(&stem);
-        let target_path = if dir_name.is_empty() {
-            install_dir.join(&stem)
-        } else {
-            install_dir.join(&dir_name)
-        };
+        return versions::install_versioned(
+            extracted_dir,
+            &target_path,
+            archive,
+            known_name,
+            is_user_level,
+            force,
+            transaction,
+        );
+    }
 
-        remove_existing_target(&target_path)?;
-        fs::create_dir_all(&target_path)?;
+    let target_path = install_dir.join(flat_folder_name(archive)?);
 
-        for file_path in &files {
-            let file_name = file_path.file_name().ok_or(TarSmithError::CannotGetFileName)?;
-            let dest = target_path.join(file_name);
-            fs::rename(file_path, &dest)?;
-        }
+    let staged_path = temp_dir.join(".tarsmith_staged");
+    fs::create_dir_all(&staged_path)?;
 
-        target_path
-    } else {
-        let stem = archive
-            .file_stem()
-            .ok_or(TarSmithError::CannotFindArchiveName)?
-            .to_string_lossy()
-            .replace(".tar", "");
+    for dir_path in &dirs {
+        let dir_name = dir_path.file_name().ok_or(TarSmithError::CannotGetDirectoryName)?;
+        let dest = staged_path.join(dir_name);
+        fs::rename(dir_path, &dest)?;
+    }
+    for file_path in &files {
+        let file_name = file_path.file_name().ok_or(TarSmithError::CannotGetFileName)?;
+        let dest = staged_path.join(file_name);
+        fs::rename(file_path, &dest)?;
+    }
 
-        let dir_name = extract_dir_name_from_stem(&stem);
-        let target_path = if dir_name.is_empty() {
-            install_dir.join(&stem)
-        } else {
-            install_dir.join(&dir_name)
-        };
+    versions::install_versioned(
+        &staged_path,
+        &target_path,
+        archive,
+        known_name,
+        is_user_level,
+        force,
+        transaction,
+    )
+}
 
-        remove_existing_target(&target_path)?;
-        fs::create_dir_all(&target_path)?;
+/// Extracts a clean application name from the extracted folder path
+/// Drops version, OS, arch and libc segments (e.g., "android-studio-2025.2.1.7-linux" -> "android-studio", "zig-linux-x86_64-0.13.0" -> "zig")
+fn infer_app_name(extracted_path: &Path) -> Result<String, TarSmithError> {
+    let folder_name = extracted_path
+        .file_name()
+        .ok_or(TarSmithError::CannotGetFolderName)?
+        .to_string_lossy();
 
-        for dir_path in &dirs {
-            let dir_name = dir_path.file_name().ok_or(TarSmithError::CannotGetDirectoryName)?;
-            let dest = target_path.join(dir_name);
-            fs::rename(dir_path, &dest)?;
-        }
-        for file_path in &files {
-            let file_name = file_path.file_name().ok_or(TarSmithError::CannotGetFileName)?;
-            let dest = target_path.join(file_name);
-            fs::rename(file_path, &dest)?;
-        }
+    app_name_of(&folder_name)
+}
 
-        target_path
-    };
+/// Parsed app name of a folder or archive stem, or the whole text when it is only separators
+/// (`_`); an empty name is refused, since the install root itself would become the app directory
+fn app_name_of(text: &str) -> Result<String, TarSmithError> {
+    let parsed = archive_name::parse(text).name;
+    let name = if parsed.is_empty() { text } else { &parsed };
+    if matches!(name, "" | "." | "..") {
+        return Err(TarSmithError::InvalidAppName(text.to_string()));
+    }
+    Ok(name.to_string())
+}
 
-    Ok(final_path)
+/// Returns an executable's file name
+fn exe_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
 }
 
-/// Finds all executable files in a directory (bin/ or root) by checking file permissions
-fn find_executables_in_bin(bin_dir: &Path) -> Result<Vec<PathBuf>, TarSmithError> {
-    let mut executables = Vec::new();
+/// Folder name for archives that do not extract into a single directory, taken from the archive name
+fn flat_folder_name(archive: &Path) -> Result<String, TarSmithError> {
+    let stem = archive
+        .file_stem()
+        .ok_or(TarSmithError::CannotFindArchiveName)?
+        .to_string_lossy()
+        .replace(".tar", "");
+    app_name_of(&stem)
+}
 
-    for entry in fs::read_dir(bin_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            let metadata = fs::metadata(&path)?;
-            let perms = metadata.permissions();
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                if perms.mode() & 0o111 != 0 {
-                    executables.push(path);
-                }
-            }
-        }
+/// Hashes the archive and checks it against `--sha256`/`--checksum-file` and `--signature`
+/// Returns the SHA-256, whether it matched an expected digest, and the verified signature format
+fn verify_archive(
+    archive_path: &Path,
+    args: &Args,
+) -> Result<(String, bool, Option<&'static str>), TarSmithError> {
+    let sha256 = checksum::sha256_file(archive_path)?;
+    let expected_sha256 = if let Some(hex) = &args.checks.sha256 {
+        Some(hex.clone())
+    } else if let Some(sums_file) = &args.checks.checksum_file {
+        Some(checksum::expected_from_sums_file(sums_file, archive_path)?)
+    } else {
+        None
+    };
+    if let Some(expected) = &expected_sha256 {
+        checksum::verify_sha256(expected, &sha256)?;
     }
 
-    if executables.is_empty() {
-        Err(TarSmithError::NoExecutableFound)
+    let signature_format = match &args.checks.signature {
+        Some(signature_file) => Some(signature::verify_signature(
+            archive_path,
+            signature_file,
+            args.checks.pubkey.as_deref(),
+        )?),
+        None => None,
+    };
+    Ok((sha256, expected_sha256.is_some(), signature_format))
+}
+
+/// Returns the directory apps are installed under: ~/.local/tarsmith or /opt
+fn install_root(is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    if is_user_level {
+        Ok(dirs::home_dir()
+            .ok_or(TarSmithError::HomeDirectoryNotFound)?
+            .join(".local/tarsmith"))
     } else {
-        Ok(executables)
+        Ok(Path::new("/opt").to_path_buf())
     }
 }
 
-/// Extracts a clean application name from the extracted folder path
-/// Removes version numbers and platform suffixes (e.g., "android-studio-2025.2.1.7-linux" -> "android-studio")
-fn infer_app_name(extracted_path: &Path) -> Result<String, TarSmithError> {
-    let folder_name = extracted_path
-        .file_name()
-        .ok_or(TarSmithError::CannotGetFolderName)?
-        .to_string_lossy();
+/// Returns the directory PATH symlinks go into: ~/.local/bin or /usr/local/bin
+fn bin_dir(is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    if is_user_level {
+        Ok(dirs::home_dir()
+            .ok_or(TarSmithError::HomeDirectoryNotFound)?
+            .join(".local/bin"))
+    } else {
+        Ok(Path::new("/usr/local/bin").to_path_buf())
+    }
+}
 
-    let name = folder_name
-        .split('-')
-        .take_while(|part| {
-            !part
-                .chars()
-                .next()
-                .map(|c| c.is_ascii_digit())
-                .unwrap_or(false)
-                && !matches!(
-                    part.to_lowercase().as_str(),
-                    "linux" | "x64" | "x86" | "amd64" | "arm64" | "aarch64"
-                )
-        })
-        .collect::<Vec<_>>()
-        .join("-");
-
-    if name.is_empty() {
-        Ok(folder_name.to_string())
+/// Returns the path of an app's desktop entry
+fn desktop_file_path(app_name: &str, is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    let desktop_filename = format!("{}.desktop", app_name);
+    if is_user_level {
+        Ok(dirs::home_dir()
+            .ok_or(TarSmithError::HomeDirectoryNotFound)?
+            .join(".local/share/applications")
+            .join(&desktop_filename))
     } else {
-        Ok(name)
+        Ok(Path::new("/usr/share/applications").join(&desktop_filename))
     }
 }
 
-/// Searches common locations for application icon files
-fn find_icon(extracted_path: &Path) -> Option<PathBuf> {
-    let common_icon_paths = vec![
-        extracted_path.join("bin").join("icon.png"),
-        extracted_path.join("bin").join("studio.png"),
-        extracted_path.join("icon.png"),
-        extracted_path.join("icon.svg"),
-        extracted_path.join("bin").join("icon.svg"),
-    ];
-
-    common_icon_paths
-        .into_iter()
-        .find(|icon_path| icon_path.exists())
+/// Renders the desktop entry written for an app; `icon` is an icon theme name, and `terminal`
+/// marks a generated entry for a terminal program
+/// A bundled entry is rewritten for the install when it can be, otherwise a minimal one is generated;
+/// the second value says which bundled file was used or why it was not, the third lists the
+/// bundled actions left out because their program is not installed
+fn desktop_entry_contents(
+    app_name: &str,
+    exec_file: &Path,
+    executables: &[PathBuf],
+    icon: Option<&str>,
+    terminal: bool,
+    bundled: Option<&desktop::BundledEntry>,
+) -> Result<(String, Option<String>, Vec<String>), TarSmithError> {
+    let mut source = None;
+    if let Some(bundled) = bundled {
+        match bundled.rewrite(exec_file, executables, icon) {
+            Ok(contents) => {
+                let note = format!("based on bundled {}", bundled.source.display());
+                let dropped = bundled
+                    .dropped_actions(exec_file, executables)
+                    .into_iter()
+                    .map(|(action, program)| {
+                        format!("dropped action {}: {} is not installed", action, program)
+                    })
+                    .collect();
+                return Ok((contents, Some(note), dropped));
+            }
+            Err(e) => {
+                source = Some(format!(
+                    "generated: bundled {} cannot be used ({})",
+                    bundled.source.display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    let mut entry = desktop::DesktopEntry::new(app_name, exec_file);
+    entry.icon = icon.map(str::to_string);
+    entry.terminal = terminal;
+    Ok((entry.render()?, source, Vec::new()))
 }
 
-/// Creates symlinks for selected executables in the appropriate bin directory
+/// How PATH entries are created: kept from the previous install on upgrades, otherwise from the flags
+fn link_settings(previous: Option<&InstallRecord>, args: &Args) -> (LinkMode, WrapperConfig) {
+    match previous {
+        Some(previous) => (previous.link_mode, previous.wrapper.clone()),
+        None => (
+            args.link_mode,
+            WrapperConfig {
+                env: args.wrapper_env.clone(),
+                cwd: args.wrapper_cwd.clone(),
+            },
+        ),
+    }
+}
+
+/// Creates symlinks, or wrapper launchers with `--link-mode wrapper`, for selected executables
+/// in the appropriate bin directory
 /// For user-level: ~/.local/bin, for system-wide: /usr/local/bin
+/// A link that would replace or shadow an existing command is skipped, renamed or forced as the
+/// flags (or, interactively, the user) decide; upgrades keep the link names of the previous version
+/// Returns each linked executable with the symlink created for it
 fn create_path_symlinks(
     executables: &[PathBuf],
+    app_name: &str,
+    app_dir: &Path,
     is_user_level: bool,
-) -> Result<(), TarSmithError> {
-    let bin_dir = if is_user_level {
-        dirs::home_dir()
-            .ok_or(TarSmithError::HomeDirectoryNotFound)?
-            .join(".local/bin")
-    } else {
-        Path::new("/usr/local/bin").to_path_buf()
-    };
+    previous: Option<&InstallRecord>,
+    args: &Args,
+    transaction: &mut Transaction,
+) -> Result<Vec<(PathBuf, PathBuf)>, TarSmithError> {
+    let bin_dir = bin_dir(is_user_level)?;
+    let interactive = previous.is_none() && !args.system && !args.user;
+    let (link_mode, wrapper) = link_settings(previous, args);
 
     if !bin_dir.exists() {
         fs::create_dir_all(&bin_dir)?;
     }
 
+    let mut created = Vec::new();
+
     #[cfg(unix)]
     {
-        use std::os::unix::fs::symlink;
+        use std::os::unix::fs::{PermissionsExt, symlink};
 
         for exec_file in executables {
-            let symlink_name = exec_file
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let symlink_path = bin_dir.join(&symlink_name);
-
-            if symlink_path.exists() || symlink_path.is_symlink() {
-                fs::remove_file(&symlink_path).ok();
+            let name = exe_name(exec_file);
+            let kept = previous.and_then(|previous| {
+                previous
+                    .symlinks
+                    .iter()
+                    .find(|link| {
+                        launcher::target(link).is_some_and(|target| {
+                            target.starts_with(app_dir) && target.file_name() == exec_file.file_name()
+                        })
+                    })
+                    .cloned()
+            });
+            let mut symlink_path = kept.unwrap_or_else(|| bin_dir.join(&name));
+
+            let mut skip = false;
+            while let Some(conflict) =
+                conflicts::find_conflict(&symlink_path, app_name, app_dir, is_user_level)
+            {
+                println!(
+                    "    Conflict: {} is {}",
+                    symlink_path.display(),
+                    conflict
+                );
+                if !interactive {
+                    let resolution = conflicts::resolution(
+                        &name,
+                        args.force,
+                        args.link_prefix.as_deref(),
+                        args.link_suffix.as_deref(),
+                    );
+                    match conflicts::resolve(
+                        &symlink_path,
+                        resolution,
+                        app_name,
+                        app_dir,
+                        is_user_level,
+                    )? {
+                        conflicts::Outcome::Forced => {
+                            println!("    Creating it anyway (--force)")
+                        }
+                        conflicts::Outcome::Renamed(renamed) => symlink_path = renamed,
+                        conflicts::Outcome::Skipped => {
+                            println!(
+                                "    Skipped {} (use --link-prefix/--link-suffix to rename it, or --force to create it anyway)",
+                                name
+                            );
+                            skip = true;
+                        }
+                        conflicts::Outcome::RenameConflicts(renamed, conflict) => {
+                            println!(
+                                "    Skipped {}: {} is {} as well",
+                                name,
+                                renamed.display(),
+                                conflict
+                            );
+                            skip = true;
+                        }
+                    }
+                    break;
+                }
+
+                print!("    Skip, rename or create it anyway? (S/r/f): ");
+                io::stdout().flush()?;
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                match response.trim().to_lowercase().as_str() {
+                    "r" | "rename" => {
+                        print!("    New command name: ");
+                        io::stdout().flush()?;
+                        let mut new_name = String::new();
+                        io::stdin().read_line(&mut new_name)?;
+                        let new_name = new_name.trim();
+                        if !conflicts::is_valid_link_name(new_name) {
+                            return Err(TarSmithError::InvalidSelection(format!(
+                                "Invalid command name: {}",
+                                new_name
+                            )));
+                        }
+                        // The new name is checked again on the next pass
+                        symlink_path = bin_dir.join(new_name);
+                    }
+                    "f" | "force" => {
+                        println!("    Creating it anyway");
+                        break;
+                    }
+                    _ => {
+                        println!(
+                            "    Skipped {} (use --link-prefix/--link-suffix to rename it, or --force to create it anyway)",
+                            name
+                        );
+                        skip = true;
+                        break;
+                    }
+                }
+            }
+            if skip {
+                continue;
             }
 
-            symlink(exec_file, &symlink_path)?;
+            transaction.prepare_write(&symlink_path)?;
+            match link_mode {
+                LinkMode::Symlink => symlink(exec_file, &symlink_path)?,
+                LinkMode::Wrapper => {
+                    let script = launcher::wrapper_script(
+                        app_name,
+                        &app_dir.join(versions::CURRENT_LINK),
+                        exec_file,
+                        &wrapper,
+                    );
+                    fs::write(&symlink_path, script)?;
+                    fs::set_permissions(&symlink_path, fs::Permissions::from_mode(0o755))?;
+                }
+            }
             println!(
-                "    Created symlink: {} -> {}",
-                symlink_name,
+                "    Created {}: {} -> {}",
+                match link_mode {
+                    LinkMode::Symlink => "symlink",
+                    LinkMode::Wrapper => "wrapper",
+                },
+                exe_name(&symlink_path),
                 exec_file.display()
             );
+            created.push((exec_file.clone(), symlink_path));
         }
     }
 
-    if is_user_level {
-        ensure_local_bin_in_path()?;
+    if !created.is_empty() {
+        let names: Vec<String> = created.iter().map(|(_, link)| exe_name(link)).collect();
+        println!(
+            "    You can now run these commands from your terminal: {}",
+            names.join(", ")
+        );
     }
 
-    let names: Vec<String> = executables
-        .iter()
-        .map(|e| {
-            e.file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string()
-        })
-        .collect();
-    println!(
-        "    You can now run these commands from your terminal: {}",
-        names.join(", ")
-    );
-
-    Ok(())
+    Ok(created)
 }
 
-/// Ensures ~/.local/bin is added to PATH by modifying the user's shell config file
-/// Detects shell type (bash/zsh/fish) and adds appropriate export statement
-fn ensure_local_bin_in_path() -> Result<(), TarSmithError> {
-    let local_bin = dirs::home_dir()
-        .ok_or(TarSmithError::HomeDirectoryNotFound)?
-        .join(".local/bin");
+/// What is needed for ~/.local/bin to be on the user's PATH
+enum PathExport {
+    /// Already on the current PATH
+    InPath,
+    /// Exported by this shell config file already
+    Exported(PathBuf),
+    /// These lines have to be appended to this shell config file
+    Append(PathBuf, Vec<String>),
+}
 
-    let local_bin_str = local_bin.to_string_lossy().to_string();
+/// Works out whether ~/.local/bin has to be exported, and where, for the user's shell (bash/zsh/fish)
+fn path_export() -> Result<PathExport, TarSmithError> {
+    let home_dir = dirs::home_dir().ok_or(TarSmithError::HomeDirectoryNotFound)?;
+    let local_bin_str = home_dir.join(".local/bin").to_string_lossy().to_string();
 
     if let Ok(path_var) = env::var("PATH") {
         let path_components: Vec<&str> = path_var.split(':').collect();
@@ -709,22 +1280,17 @@ fn ensure_local_bin_in_path() -> Result<(), TarSmithError> {
             .iter()
             .any(|p| p == &local_bin_str || p.ends_with(".local/bin"))
         {
-            println!("[7] ~/.local/bin is already in PATH ✔");
-            return Ok(());
+            return Ok(PathExport::InPath);
         }
     }
 
     let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
-    let home_dir = dirs::home_dir().ok_or(TarSmithError::HomeDirectoryNotFound)?;
     let (config_file, path_export) = if shell.contains("zsh") {
         let file = home_dir.join(".zshrc");
         let export = "export PATH=\"$HOME/.local/bin:$PATH\"";
         (file, export)
     } else if shell.contains("fish") {
         let file = home_dir.join(".config/fish/config.fish");
-        if let Some(parent) = file.parent() {
-            fs::create_dir_all(parent).ok();
-        }
         let export = "set -gx PATH $HOME/.local/bin $PATH";
         (file, export)
     } else {
@@ -739,6 +1305,28 @@ fn ensure_local_bin_in_path() -> Result<(), TarSmithError> {
             || contents.contains("~/.local/bin")
             || contents.contains(".local/bin")
         {
+            return Ok(PathExport::Exported(config_file));
+        }
+    }
+
+    Ok(PathExport::Append(
+        config_file,
+        vec![PATH_EXPORT_MARKER.to_string(), path_export.to_string()],
+    ))
+}
+
+/// Ensures ~/.local/bin is added to PATH by modifying the user's shell config file
+/// Returns the config file this install appended the export to; an export that was already
+/// there belongs to whoever added it and is not returned
+fn ensure_local_bin_in_path(
+    transaction: &mut Transaction,
+) -> Result<Option<PathBuf>, TarSmithError> {
+    let (config_file, lines) = match path_export()? {
+        PathExport::InPath => {
+            println!("[7] ~/.local/bin is already in PATH ✔");
+            return Ok(None);
+        }
+        PathExport::Exported(config_file) => {
             println!(
                 "[7] ~/.local/bin export found in {} ✔",
                 config_file.display()
@@ -747,18 +1335,23 @@ fn ensure_local_bin_in_path() -> Result<(), TarSmithError> {
                 "    Note: You may need to restart your terminal or run: source {}",
                 config_file.display()
             );
-            return Ok(());
+            return Ok(None);
         }
-    }
+        PathExport::Append(config_file, lines) => (config_file, lines),
+    };
 
+    if let Some(parent) = config_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    transaction.prepare_append(&config_file)?;
     let mut file = fs::OpenOptions::new()
         .create(true)
         .append(true)
         .open(&config_file)?;
 
-    use std::io::Write;
-    writeln!(file, "# Added by TarSmith installer")?;
-    writeln!(file, "{}", path_export)?;
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
 
     println!(
         "[7] Added ~/.local/bin to PATH in {} ✔",
@@ -769,5 +1362,5 @@ fn ensure_local_bin_in_path() -> Result<(), TarSmithError> {
         config_file.display()
     );
 
-    Ok(())
+    Ok(Some(config_file))
 }
@@ -0,0 +1,556 @@
+use crate::conflicts::{self, Outcome};
+use crate::extract::{self, ListedEntry, ListedKind};
+use crate::launcher::{self, LinkMode, WrapperConfig};
+use crate::tree::AppTree;
+use crate::upgrade::{self, Retired};
+use crate::{
+    Args, PathExport, TarSmithError, bin_dir, caches, desktop, desktop_entry_contents,
+    desktop_file_path, exe_name, flat_folder_name, gui, icons, infer_app_name, install_root,
+    path_export, registry, selection, verify_archive, versions,
+};
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What an install would do to a path
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Create,
+    Replace,
+    Append,
+    Remove,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Create => "create",
+            Action::Replace => "replace",
+            Action::Append => "append",
+            Action::Remove => "remove",
+        })
+    }
+}
+
+/// Kind of path a change touches
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PathKind {
+    Dir,
+    File,
+    Symlink,
+}
+
+impl fmt::Display for PathKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PathKind::Dir => "dir",
+            PathKind::File => "file",
+            PathKind::Symlink => "symlink",
+        })
+    }
+}
+
+/// One filesystem change an install would make
+#[derive(Serialize, Debug)]
+struct Change {
+    action: Action,
+    kind: PathKind,
+    path: PathBuf,
+    /// Where a symlink would point
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<PathBuf>,
+    /// What an existing symlink or wrapper at `path` launches now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_target: Option<PathBuf>,
+    /// Contents written, or lines appended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contents: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+impl Change {
+    fn new(action: Action, kind: PathKind, path: &Path) -> Self {
+        Change {
+            action,
+            kind,
+            path: path.to_path_buf(),
+            target: None,
+            current_target: None,
+            contents: None,
+            note: None,
+        }
+    }
+
+    /// A change to `path` that creates it, or replaces whatever is there now
+    fn at(kind: PathKind, path: &Path) -> Self {
+        let action = if path.symlink_metadata().is_ok() {
+            Action::Replace
+        } else {
+            Action::Create
+        };
+        let mut change = Change::new(action, kind, path);
+        change.current_target = launcher::target(path);
+        change
+    }
+
+    fn symlink(path: &Path, target: &Path) -> Self {
+        let mut change = Change::at(PathKind::Symlink, path);
+        change.target = Some(target.to_path_buf());
+        change
+    }
+
+    fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// An archive entry as shown in the plan
+#[derive(Serialize, Debug)]
+struct PlannedEntry {
+    path: PathBuf,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<PathBuf>,
+    size: u64,
+    executable: bool,
+}
+
+/// A PATH link that clashes with an existing command, and what the install would do about it
+#[derive(Serialize, Debug)]
+struct LinkConflict {
+    command: String,
+    link: PathBuf,
+    conflict: String,
+    resolution: String,
+}
+
+/// Everything an install would do, worked out from the archive's listing without touching disk
+#[derive(Serialize, Debug)]
+struct Plan {
+    archive: PathBuf,
+    format: String,
+    sha256: String,
+    sha256_verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    install_type: &'static str,
+    app_name: String,
+    version: String,
+    /// Architecture of the native executables, when every one of them runs here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    architecture: Option<String>,
+    /// Executables built for another machine, and program interpreters that are not installed
+    warnings: Vec<String>,
+    executables: Vec<String>,
+    desktop_exec: Option<String>,
+    /// Whether the desktop executable is graphical or a terminal program, and why
+    #[serde(skip_serializing_if = "Option::is_none")]
+    desktop_interface: Option<String>,
+    path_execs: Vec<String>,
+    /// PATH links that would clobber or shadow an existing command
+    link_conflicts: Vec<LinkConflict>,
+    contents: Vec<PlannedEntry>,
+    changes: Vec<Change>,
+}
+
+/// Prints what installing the archive would do, as text or JSON, without extracting it or
+/// writing anything; selections follow the non-interactive defaults and flags
+pub fn dry_run(archive_path: &Path, args: &Args) -> Result<(), TarSmithError> {
+    let plan = build_plan(archive_path, args)?;
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&plan).map_err(io::Error::from)?
+        );
+    } else {
+        print_plan(&plan);
+    }
+    Ok(())
+}
+
+fn build_plan(archive_path: &Path, args: &Args) -> Result<Plan, TarSmithError> {
+    if !archive_path.exists() {
+        return Err(TarSmithError::ArchiveNotFound(archive_path.to_path_buf()));
+    }
+    let (sha256, sha256_verified, signature) = verify_archive(archive_path, args)?;
+    let (format, entries) = extract::list_archive(archive_path)?;
+
+    // Mirrors analyze_and_move_extraction: a lone top-level directory becomes the app root
+    let mut top_dirs: Vec<PathBuf> = Vec::new();
+    let mut top_files = 0;
+    for entry in &entries {
+        let Some(first) = entry.path.iter().next().map(PathBuf::from) else {
+            continue;
+        };
+        if entry.path.iter().count() > 1 || entry.kind == ListedKind::Dir {
+            if !top_dirs.contains(&first) {
+                top_dirs.push(first);
+            }
+        } else {
+            top_files += 1;
+        }
+    }
+    if top_dirs.is_empty() && top_files == 0 {
+        return Err(TarSmithError::ArchiveEmpty);
+    }
+    let (prefix, folder_name) = if top_dirs.len() == 1 && top_files == 0 {
+        let folder_name = top_dirs[0].to_string_lossy().to_string();
+        (top_dirs[0].clone(), folder_name)
+    } else {
+        (PathBuf::new(), flat_folder_name(archive_path)?)
+    };
+
+    let is_user_level = !args.system;
+    let root = install_root(is_user_level)?;
+    let app_name = infer_app_name(Path::new(&folder_name))?;
+    let version = versions::version_of(&folder_name, archive_path)?;
+    let app_dir = root.join(&app_name);
+    versions::check_app_dir(&app_dir, &app_name, is_user_level, args.force)?;
+    let current = app_dir.join(versions::CURRENT_LINK);
+    let version_dir = app_dir.join(&version);
+
+    let tree = AppTree::Listing {
+        entries: &entries,
+        prefix: &prefix,
+        root: &current,
+    };
+    let selection = selection::select(&tree, &app_name, args, None, false)?;
+    let executables = &selection.executables;
+    let desktop_exec = selection.desktop.exec();
+    let desktop_interface = desktop_exec.and_then(|exec| selection.interface(exec));
+    let path_execs = selection.path.execs();
+
+    let mut changes = Vec::new();
+    if !root.exists() {
+        changes.push(Change::new(Action::Create, PathKind::Dir, &root));
+    }
+    let extracted = format!(
+        "{} entries extracted from the archive",
+        entries
+            .iter()
+            .filter(|e| e.path.starts_with(&prefix) && e.path != prefix)
+            .count()
+    );
+    if current.is_symlink() {
+        let change = Change::at(PathKind::Dir, &version_dir);
+        let note = if change.action == Action::Replace {
+            format!(
+                "{}; the installed copy of this version is kept as a backup until the install succeeds, then deleted",
+                extracted
+            )
+        } else {
+            extracted
+        };
+        changes.push(change.note(note));
+    } else {
+        let mut change = Change::at(PathKind::Dir, &app_dir);
+        if change.action == Action::Replace {
+            change = change.note(
+                "existing directory is not a versioned install; it is kept as a backup until the install succeeds, then deleted",
+            );
+        }
+        changes.push(change);
+        changes.push(Change::new(Action::Create, PathKind::Dir, &version_dir).note(extracted));
+    }
+    changes.push(Change::symlink(&current, Path::new(&version)));
+
+    let mut desktop_file = None;
+    let mut icon_files = Vec::new();
+    if let Some(exec_file) = desktop_exec {
+        let desktop_path = desktop_file_path(&app_name, is_user_level)?;
+        if let Some(parent) = desktop_path.parent()
+            && !parent.exists()
+        {
+            changes.push(Change::new(Action::Create, PathKind::Dir, parent));
+        }
+        let theme_dir = icons::theme_dir(is_user_level)?;
+        let found_icons = icons::find_icons(&tree, &app_name)?;
+        for icon in &found_icons {
+            let target = icon.theme_path(&theme_dir, &app_name);
+            if icons::is_foreign(&target, is_user_level) {
+                continue;
+            }
+            changes.push(
+                Change::at(PathKind::File, &target)
+                    .note(format!("icon copied from {}", icon.source.display())),
+            );
+            icon_files.push(target);
+        }
+        let bundled = desktop::find_bundled(&tree, &app_name, &exe_name(exec_file))?;
+        let (contents, source, dropped) = desktop_entry_contents(
+            &app_name,
+            exec_file,
+            executables,
+            (!found_icons.is_empty()).then_some(app_name.as_str()),
+            desktop_interface.is_some_and(gui::Interface::is_terminal),
+            bundled.as_ref(),
+        )?;
+        let mut change = Change::at(PathKind::File, &desktop_path);
+        change.contents = Some(contents);
+        if let Some(source) = source {
+            change = change.note(
+                std::iter::once(source)
+                    .chain(dropped)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            );
+        }
+        changes.push(change);
+        if let Some(applications_dir) = desktop_path.parent() {
+            changes.push(
+                Change::at(PathKind::File, &applications_dir.join(caches::MIME_CACHE))
+                    .note("regenerated from the installed desktop entries"),
+            );
+        }
+        desktop_file = Some(desktop_path);
+    }
+
+    let bin_dir = bin_dir(is_user_level)?;
+    let wrapper = WrapperConfig {
+        env: args.wrapper_env.clone(),
+        cwd: args.wrapper_cwd.clone(),
+    };
+    let path_entry = |link: &Path, exe: &Path| match args.link_mode {
+        LinkMode::Symlink => Change::symlink(link, exe),
+        LinkMode::Wrapper => {
+            let mut change = Change::at(PathKind::File, link);
+            change.target = Some(exe.to_path_buf());
+            change.contents = Some(launcher::wrapper_script(&app_name, &current, exe, &wrapper));
+            change
+        }
+    };
+    let mut links = Vec::new();
+    let mut symlinks = Vec::new();
+    let mut link_conflicts = Vec::new();
+    for exe in path_execs {
+        let name = exe_name(exe);
+        let link = bin_dir.join(&name);
+        let Some(conflict) = conflicts::find_conflict(&link, &app_name, &app_dir, is_user_level)
+        else {
+            links.push(path_entry(&link, exe));
+            symlinks.push(link);
+            continue;
+        };
+
+        let resolution = conflicts::resolution(
+            &name,
+            args.force,
+            args.link_prefix.as_deref(),
+            args.link_suffix.as_deref(),
+        );
+        let resolution =
+            match conflicts::resolve(&link, resolution, &app_name, &app_dir, is_user_level)? {
+                Outcome::Forced => {
+                    links.push(path_entry(&link, exe));
+                    symlinks.push(link.clone());
+                    "created anyway (--force)".to_string()
+                }
+                Outcome::Renamed(renamed) => {
+                    links.push(path_entry(&renamed, exe));
+                    let resolution = format!("renamed to {}", exe_name(&renamed));
+                    symlinks.push(renamed);
+                    resolution
+                }
+                Outcome::Skipped => "skipped".to_string(),
+                Outcome::RenameConflicts(renamed, other) => {
+                    format!("skipped, {} is {} as well", renamed.display(), other)
+                }
+            };
+        link_conflicts.push(LinkConflict {
+            command: name,
+            link,
+            conflict: conflict.to_string(),
+            resolution,
+        });
+    }
+
+    if !links.is_empty() {
+        if !bin_dir.exists() {
+            changes.push(Change::new(Action::Create, PathKind::Dir, &bin_dir));
+        }
+        changes.append(&mut links);
+
+        if is_user_level && let PathExport::Append(config_file, lines) = path_export()? {
+            let action = if config_file.exists() {
+                Action::Append
+            } else {
+                Action::Create
+            };
+            let mut change = Change::new(action, PathKind::File, &config_file);
+            change.contents = Some(lines.join("\n") + "\n");
+            changes.push(change);
+        }
+    }
+
+    // An install over an installed app retires what its record tracks and this one no longer creates
+    if let Ok(existing) = registry::load_record(&app_name, is_user_level) {
+        for retired in upgrade::retired(&existing, &symlinks, desktop_file.as_deref(), &icon_files)
+        {
+            let kind = match retired {
+                Retired::Symlink(_) => PathKind::Symlink,
+                Retired::DesktopEntry(_) | Retired::Icon(_) => PathKind::File,
+            };
+            let mut change = Change::new(Action::Remove, kind, retired.path());
+            change.current_target = launcher::target(retired.path());
+            changes.push(change.note("left over from the installed version"));
+        }
+    }
+
+    changes.push(
+        Change::at(
+            PathKind::File,
+            &registry::record_path(&app_name, is_user_level)?,
+        )
+        .note("install record"),
+    );
+
+    Ok(Plan {
+        archive: archive_path.to_path_buf(),
+        format: format.to_string(),
+        sha256,
+        sha256_verified,
+        signature: signature.map(str::to_string),
+        install_type: if is_user_level { "user" } else { "system" },
+        app_name,
+        version,
+        architecture: selection.arch.matched.clone(),
+        warnings: selection.arch.warnings.clone(),
+        executables: executables.iter().map(|e| exe_name(e)).collect(),
+        desktop_exec: desktop_exec.map(|e| exe_name(e)),
+        desktop_interface: desktop_interface.map(ToString::to_string),
+        path_execs: path_execs.iter().map(|e| exe_name(e)).collect(),
+        link_conflicts,
+        contents: entries.iter().map(planned_entry).collect(),
+        changes,
+    })
+}
+
+fn planned_entry(entry: &ListedEntry) -> PlannedEntry {
+    let (kind, target) = match &entry.kind {
+        ListedKind::File => ("file", None),
+        ListedKind::Dir => ("dir", None),
+        ListedKind::Symlink(target) => ("symlink", Some(target.clone())),
+        ListedKind::Other => ("other", None),
+    };
+    PlannedEntry {
+        path: entry.path.clone(),
+        kind,
+        target,
+        size: entry.size,
+        executable: entry.kind == ListedKind::File && entry.mode & 0o111 != 0,
+    }
+}
+
+fn print_plan(plan: &Plan) {
+    let none = || "none".to_string();
+
+    println!("=== TarSmith Dry Run ===");
+    println!(
+        "Archive: {} ({}, {} entries)",
+        plan.archive.display(),
+        plan.format,
+        plan.contents.len()
+    );
+    println!(
+        "SHA-256: {}{}",
+        plan.sha256,
+        if plan.sha256_verified {
+            " (verified)"
+        } else {
+            ""
+        }
+    );
+    if let Some(signature) = &plan.signature {
+        println!("Signature: {} verified", signature);
+    }
+    println!(
+        "Install type: {}",
+        if plan.install_type == "user" {
+            "user-level"
+        } else {
+            "system-wide"
+        }
+    );
+    println!("App: {} {}", plan.app_name, plan.version);
+    if let Some(architecture) = &plan.architecture {
+        println!("Architecture: {} matches this machine", architecture);
+    }
+    for warning in &plan.warnings {
+        println!("Warning: {}", warning);
+    }
+    println!("Executables: {}", plan.executables.join(", "));
+    match (&plan.desktop_exec, &plan.desktop_interface) {
+        (Some(exec), Some(interface)) => {
+            println!("Desktop entry executable: {}, {}", exec, interface)
+        }
+        (Some(exec), None) => println!("Desktop entry executable: {}", exec),
+        (None, _) => println!("Desktop entry executable: {}", none()),
+    }
+    println!(
+        "PATH executables: {}",
+        if plan.path_execs.is_empty() {
+            none()
+        } else {
+            plan.path_execs.join(", ")
+        }
+    );
+
+    for conflict in &plan.link_conflicts {
+        println!(
+            "Conflict: {} is {}; {}",
+            conflict.link.display(),
+            conflict.conflict,
+            conflict.resolution
+        );
+    }
+
+    println!();
+    println!("Archive contents:");
+    for entry in &plan.contents {
+        match (&entry.target, entry.kind) {
+            (Some(target), _) => {
+                println!("    {} -> {}", entry.path.display(), target.display())
+            }
+            (None, "dir") => println!("    {}/", entry.path.display()),
+            (None, _) => println!(
+                "    {}{}",
+                entry.path.display(),
+                if entry.executable { " *" } else { "" }
+            ),
+        }
+    }
+
+    println!();
+    println!("Planned changes:");
+    for change in &plan.changes {
+        let mut line = format!(
+            "  {:<8} {:<8} {}",
+            change.action.to_string(),
+            change.kind.to_string(),
+            change.path.display()
+        );
+        if let Some(target) = &change.target {
+            line.push_str(&format!(" -> {}", target.display()));
+        }
+        if let Some(current_target) = &change.current_target {
+            line.push_str(&format!(" (currently -> {})", current_target.display()));
+        }
+        println!("{}", line);
+        if let Some(note) = &change.note {
+            println!("                    ({})", note);
+        }
+        if let Some(contents) = &change.contents {
+            for content_line in contents.lines() {
+                println!("                    | {}", content_line);
+            }
+        }
+    }
+
+    println!();
+    println!("Dry run: nothing was extracted or written.");
+}
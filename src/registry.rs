@@ -0,0 +1,166 @@
+use crate::TarSmithError;
+use crate::launcher::{LinkMode, WrapperConfig};
+use crate::versions;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Everything an install created on disk, persisted as one JSON manifest per app
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstallRecord {
+    pub app_name: String,
+    pub source_archive: PathBuf,
+    pub sha256: String,
+    #[serde(default)]
+    pub sha256_verified: bool,
+    pub install_dir: PathBuf,
+    #[serde(default)]
+    pub version: String,
+    pub is_user_level: bool,
+    #[serde(default)]
+    pub symlinks: Vec<PathBuf>,
+    #[serde(default)]
+    pub desktop_file: Option<PathBuf>,
+    /// Icons copied into the hicolor theme for the desktop entry
+    #[serde(default)]
+    pub icons: Vec<PathBuf>,
+    /// File name of the executable chosen for the desktop entry
+    #[serde(default)]
+    pub desktop_exec: Option<String>,
+    /// File names of the executables chosen for PATH
+    #[serde(default)]
+    pub path_execs: Vec<String>,
+    /// File names of every executable the installed version shipped
+    #[serde(default)]
+    pub executables: Vec<String>,
+    /// Whether PATH entries are symlinks or wrapper launchers
+    #[serde(default)]
+    pub link_mode: LinkMode,
+    /// Environment and working directory wrapper launchers set up
+    #[serde(default)]
+    pub wrapper: WrapperConfig,
+    #[serde(default)]
+    pub shell_rc: Option<PathBuf>,
+    pub installed_at: u64,
+}
+
+/// Returns the registry directory: ~/.local/share/tarsmith for user-level, /var/lib/tarsmith for system-wide
+pub fn registry_dir(is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    if is_user_level {
+        Ok(dirs::home_dir()
+            .ok_or(TarSmithError::HomeDirectoryNotFound)?
+            .join(".local/share/tarsmith"))
+    } else {
+        Ok(Path::new("/var/lib/tarsmith").to_path_buf())
+    }
+}
+
+/// Returns the manifest path for an app
+pub fn record_path(app_name: &str, is_user_level: bool) -> Result<PathBuf, TarSmithError> {
+    Ok(registry_dir(is_user_level)?.join(format!("{}.json", app_name)))
+}
+
+/// Writes the manifest for an install, replacing any previous record for the same app
+pub fn save_record(record: &InstallRecord) -> Result<PathBuf, TarSmithError> {
+    let path = record_path(&record.app_name, record.is_user_level)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(record)
+        .map_err(|e| TarSmithError::InvalidRecord(path.clone(), e.to_string()))?;
+    fs::write(&path, contents)?;
+    Ok(path)
+}
+
+/// Loads the manifest for a single app
+pub fn load_record(app_name: &str, is_user_level: bool) -> Result<InstallRecord, TarSmithError> {
+    let path = record_path(app_name, is_user_level)?;
+    if !path.exists() {
+        return Err(TarSmithError::AppNotInstalled(app_name.to_string()));
+    }
+
+    let contents = fs::read_to_string(&path)?;
+    serde_json::from_str(&contents).map_err(|e| TarSmithError::InvalidRecord(path, e.to_string()))
+}
+
+/// Deletes the manifest for an app, if present
+pub fn remove_record(app_name: &str, is_user_level: bool) -> Result<(), TarSmithError> {
+    let path = record_path(app_name, is_user_level)?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Loads every manifest in the registry for the given scope, sorted by app name
+pub fn load_records(is_user_level: bool) -> Result<Vec<InstallRecord>, TarSmithError> {
+    let dir = registry_dir(is_user_level)?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut records = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let contents = fs::read_to_string(&path)?;
+        let record: InstallRecord = serde_json::from_str(&contents)
+            .map_err(|e| TarSmithError::InvalidRecord(path.clone(), e.to_string()))?;
+        records.push(record);
+    }
+
+    records.sort_by(|a, b| a.app_name.cmp(&b.app_name));
+    Ok(records)
+}
+
+/// Prints every recorded install, user-level first, then system-wide
+pub fn list_installs() -> Result<(), TarSmithError> {
+    let mut records = load_records(true)?;
+    records.extend(load_records(false)?);
+
+    if records.is_empty() {
+        println!("No apps installed by TarSmith.");
+        return Ok(());
+    }
+
+    for record in &records {
+        println!(
+            "{} ({})",
+            record.app_name,
+            if record.is_user_level {
+                "user"
+            } else {
+                "system"
+            }
+        );
+        println!("  Installed to: {}", record.install_dir.display());
+        if !record.version.is_empty() {
+            println!(
+                "  Version: {} (installed: {})",
+                record.version,
+                versions::installed_versions(&record.install_dir)?.join(", ")
+            );
+        }
+        println!("  Source: {}", record.source_archive.display());
+        println!(
+            "  SHA-256: {}{}",
+            record.sha256,
+            if record.sha256_verified {
+                " (verified)"
+            } else {
+                ""
+            }
+        );
+        for symlink in &record.symlinks {
+            println!("  Symlink: {}", symlink.display());
+        }
+        if let Some(desktop_file) = &record.desktop_file {
+            println!("  Desktop entry: {}", desktop_file.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::elf::{self, ArchCheck};
+use crate::registry::InstallRecord;
+use crate::tree::AppTree;
+use crate::{Args, TarSmithError, desktop, discover, exe_name, gui};
+use std::path::{Path, PathBuf};
+
+/// How the executable for the desktop entry is picked
+#[derive(Debug)]
+pub enum DesktopChoice {
+    /// The previous install's executable, named, if this version still ships it
+    Kept(String, Option<PathBuf>),
+    /// No desktop entry, by `--no-desktop` or because the previous install had none
+    Skipped,
+    /// The first executable matching `--desktop-exec`
+    Matched(String, PathBuf),
+    /// The top-ranked graphical executable, the non-interactive default
+    Gui(PathBuf),
+    /// No graphical executable to default to, so no desktop entry
+    NoGui,
+    /// The user is asked
+    Ask,
+}
+
+impl DesktopChoice {
+    pub fn exec(&self) -> Option<&PathBuf> {
+        match self {
+            DesktopChoice::Kept(_, exec) => exec.as_ref(),
+            DesktopChoice::Matched(_, exec) | DesktopChoice::Gui(exec) => Some(exec),
+            DesktopChoice::Skipped | DesktopChoice::NoGui | DesktopChoice::Ask => None,
+        }
+    }
+}
+
+/// How the executables linked into PATH are picked
+#[derive(Debug)]
+pub enum PathChoice {
+    /// The previous install's selection, as far as this version still ships it
+    Kept(Vec<PathBuf>),
+    /// None, by `--no-path`
+    Skipped,
+    /// The executables matching `--path-exec`
+    Matched(Vec<PathBuf>),
+    /// Every executable, the non-interactive default
+    All(Vec<PathBuf>),
+    /// The user is asked
+    Ask,
+}
+
+impl PathChoice {
+    pub fn execs(&self) -> &[PathBuf] {
+        match self {
+            PathChoice::Kept(execs) | PathChoice::Matched(execs) | PathChoice::All(execs) => execs,
+            PathChoice::Skipped | PathChoice::Ask => &[],
+        }
+    }
+}
+
+/// The app's executables and what an install does with them
+pub struct Selection {
+    /// Ranked as `discover::find_executables` returns them
+    pub executables: Vec<PathBuf>,
+    /// Whether each executable is graphical, in the same order
+    pub interfaces: Vec<gui::Interface>,
+    pub arch: ArchCheck,
+    pub desktop: DesktopChoice,
+    pub path: PathChoice,
+}
+
+impl Selection {
+    pub fn interface(&self, exe: &Path) -> Option<&gui::Interface> {
+        self.executables
+            .iter()
+            .position(|e| e == exe)
+            .map(|i| &self.interfaces[i])
+    }
+}
+
+/// Finds, checks and classifies the app's executables and picks the desktop and PATH ones from
+/// the flags, an upgrade's `previous` record or the defaults; with `interactive` set, picks the
+/// flags leave open are left to the user
+/// Installs and dry runs both decide through this, so a plan shows what the install will do
+pub fn select(
+    tree: &AppTree,
+    app_name: &str,
+    args: &Args,
+    previous: Option<&InstallRecord>,
+    interactive: bool,
+) -> Result<Selection, TarSmithError> {
+    let executables = discover::find_executables(tree, app_name)?;
+    let native: Vec<_> = executables
+        .iter()
+        .filter_map(|exe| tree.elf_info(exe).map(|info| (exe.clone(), info)))
+        .collect();
+    let arch = elf::check_architecture(&native, args.checks.ignore_arch)?;
+
+    let bundled_program = desktop::find_bundled(
+        tree,
+        app_name,
+        &executables.first().map(|e| exe_name(e)).unwrap_or_default(),
+    )?
+    .and_then(|bundled| bundled.program());
+    let interfaces: Vec<gui::Interface> = executables
+        .iter()
+        .map(|exe| gui::classify(tree, exe, bundled_program.as_deref()))
+        .collect();
+
+    let desktop = if let Some(previous) = previous {
+        match &previous.desktop_exec {
+            Some(name) => DesktopChoice::Kept(
+                name.clone(),
+                executables.iter().find(|e| exe_name(e) == *name).cloned(),
+            ),
+            None => DesktopChoice::Skipped,
+        }
+    } else if args.no_desktop {
+        DesktopChoice::Skipped
+    } else if let Some(pattern) = &args.desktop_exec {
+        let matched = discover::select_matching(&executables, std::slice::from_ref(pattern))?;
+        DesktopChoice::Matched(pattern.clone(), matched[0].clone())
+    } else if interactive {
+        DesktopChoice::Ask
+    } else {
+        executables
+            .iter()
+            .zip(&interfaces)
+            .find(|(_, interface)| !interface.is_terminal())
+            .map_or(DesktopChoice::NoGui, |(exe, _)| {
+                DesktopChoice::Gui(exe.clone())
+            })
+    };
+
+    let path = if let Some(previous) = previous {
+        PathChoice::Kept(
+            executables
+                .iter()
+                .filter(|e| previous.path_execs.contains(&exe_name(e)))
+                .cloned()
+                .collect(),
+        )
+    } else if args.no_path {
+        PathChoice::Skipped
+    } else if !args.path_exec.is_empty() {
+        PathChoice::Matched(discover::select_matching(&executables, &args.path_exec)?)
+    } else if interactive {
+        PathChoice::Ask
+    } else {
+        PathChoice::All(executables.clone())
+    };
+
+    Ok(Selection {
+        executables,
+        interfaces,
+        arch,
+        desktop,
+        path,
+    })
+}
@@ -0,0 +1,205 @@
+use crate::TarSmithError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use pgp::composed::{Deserializable, DetachedSignature, SignedPublicKey};
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Detached signature formats accepted by `--signature`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignatureFormat {
+    Minisign,
+    Signify,
+    OpenPgp,
+}
+
+impl SignatureFormat {
+    fn name(self) -> &'static str {
+        match self {
+            SignatureFormat::Minisign => "minisign",
+            SignatureFormat::Signify => "signify",
+            SignatureFormat::OpenPgp => "OpenPGP",
+        }
+    }
+
+    /// Tells the formats apart: armored or binary OpenPGP packets, otherwise
+    /// minisign (which carries a trusted comment) or signify (which does not)
+    fn detect(contents: &[u8]) -> Self {
+        if contents.starts_with(b"-----BEGIN PGP")
+            || contents.first().is_some_and(|b| b & 0x80 != 0)
+        {
+            return SignatureFormat::OpenPgp;
+        }
+
+        let text = String::from_utf8_lossy(contents);
+        if text
+            .lines()
+            .nth(2)
+            .is_some_and(|line| line.starts_with("trusted comment:"))
+        {
+            SignatureFormat::Minisign
+        } else {
+            SignatureFormat::Signify
+        }
+    }
+}
+
+fn failed(message: impl Into<String>) -> TarSmithError {
+    TarSmithError::SignatureVerificationFailed(message.into())
+}
+
+/// Returns the keys pinned in ~/.config/tarsmith/keys, sorted by file name
+fn pinned_keys() -> Result<Vec<PathBuf>, TarSmithError> {
+    let dir = dirs::home_dir()
+        .ok_or(TarSmithError::HomeDirectoryNotFound)?
+        .join(".config/tarsmith/keys");
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut keys = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            keys.push(path);
+        }
+    }
+    keys.sort();
+    Ok(keys)
+}
+
+/// Verifies a detached signature over the archive, offline
+/// Uses `pubkey` when given, otherwise tries every pinned key; returns the signature format name
+pub fn verify_signature(
+    archive: &Path,
+    signature_file: &Path,
+    pubkey: Option<&Path>,
+) -> Result<&'static str, TarSmithError> {
+    let signature = fs::read(signature_file)?;
+    let format = SignatureFormat::detect(&signature);
+
+    let keys = match pubkey {
+        Some(key) => vec![key.to_path_buf()],
+        None => pinned_keys()?,
+    };
+    if keys.is_empty() {
+        return Err(failed(
+            "no --pubkey given and no keys pinned in ~/.config/tarsmith/keys",
+        ));
+    }
+
+    let mut last_error = None;
+    for key in &keys {
+        let key_contents = fs::read(key)?;
+        let result = match format {
+            SignatureFormat::Minisign => verify_minisign(archive, &signature, &key_contents),
+            SignatureFormat::Signify => verify_signify(archive, &signature, &key_contents),
+            SignatureFormat::OpenPgp => verify_openpgp(archive, &signature, &key_contents),
+        };
+        match result {
+            Ok(()) => return Ok(format.name()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| failed("no usable public key")))
+}
+
+/// Verifies a minisign signature, streaming the archive for prehashed signatures
+fn verify_minisign(archive: &Path, signature: &[u8], key: &[u8]) -> Result<(), TarSmithError> {
+    let signature = minisign_verify::Signature::decode(&String::from_utf8_lossy(signature))
+        .map_err(|e| failed(format!("minisign signature: {}", e)))?;
+    let key = minisign_verify::PublicKey::decode(&String::from_utf8_lossy(key))
+        .map_err(|e| failed(format!("minisign public key: {}", e)))?;
+
+    match key.verify_stream(&signature) {
+        Ok(mut verifier) => {
+            let mut file = BufReader::new(File::open(archive)?);
+            let mut buffer = [0u8; 64 * 1024];
+            loop {
+                let read = file.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                verifier.update(&buffer[..read]);
+            }
+            verifier.finalize()
+        }
+        Err(minisign_verify::Error::UnsupportedLegacyMode) => {
+            key.verify(&fs::read(archive)?, &signature, true)
+        }
+        Err(e) => Err(e),
+    }
+    .map_err(|e| failed(format!("minisign: {}", e)))
+}
+
+/// Decodes the base64 payload line of a signify key or signature file
+fn signify_payload(
+    contents: &[u8],
+    expected_len: usize,
+    what: &str,
+) -> Result<Vec<u8>, TarSmithError> {
+    let text = String::from_utf8_lossy(contents);
+    let line = text
+        .lines()
+        .find(|line| !line.starts_with("untrusted comment:") && !line.trim().is_empty())
+        .ok_or_else(|| failed(format!("signify {}: missing payload", what)))?;
+    let bytes = STANDARD
+        .decode(line.trim())
+        .map_err(|e| failed(format!("signify {}: {}", what, e)))?;
+    if bytes.len() != expected_len || &bytes[..2] != b"Ed" {
+        return Err(failed(format!("signify {}: not an Ed25519 {}", what, what)));
+    }
+    Ok(bytes)
+}
+
+/// Verifies an OpenBSD signify signature: a plain Ed25519 signature over the whole file
+fn verify_signify(archive: &Path, signature: &[u8], key: &[u8]) -> Result<(), TarSmithError> {
+    let signature = signify_payload(signature, 74, "signature")?;
+    let key = signify_payload(key, 42, "public key")?;
+    if signature[2..10] != key[2..10] {
+        return Err(failed("signify: signature was made by a different key"));
+    }
+
+    let key_bytes: [u8; 32] = key[10..42].try_into().expect("length checked");
+    let signature_bytes: [u8; 64] = signature[10..74].try_into().expect("length checked");
+    let key = VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|e| failed(format!("signify public key: {}", e)))?;
+
+    key.verify(
+        &fs::read(archive)?,
+        &Signature::from_bytes(&signature_bytes),
+    )
+    .map_err(|e| failed(format!("signify: {}", e)))
+}
+
+/// Verifies an armored or binary OpenPGP detached signature against a key or any of its subkeys
+fn verify_openpgp(archive: &Path, signature: &[u8], key: &[u8]) -> Result<(), TarSmithError> {
+    let signature = if signature.starts_with(b"-----BEGIN") {
+        DetachedSignature::from_armor_single(signature).map(|(sig, _)| sig)
+    } else {
+        DetachedSignature::from_bytes(signature)
+    }
+    .map_err(|e| failed(format!("OpenPGP signature: {}", e)))?;
+
+    let key = if key.starts_with(b"-----BEGIN") {
+        SignedPublicKey::from_armor_single(key).map(|(key, _)| key)
+    } else {
+        SignedPublicKey::from_bytes(key)
+    }
+    .map_err(|e| failed(format!("OpenPGP public key: {}", e)))?;
+
+    let open =
+        || -> Result<BufReader<File>, TarSmithError> { Ok(BufReader::new(File::open(archive)?)) };
+
+    let mut result = signature.signature.verify(&key, open()?);
+    for subkey in &key.public_subkeys {
+        if result.is_ok() {
+            break;
+        }
+        result = signature.signature.verify(subkey, open()?);
+    }
+    result.map_err(|e| failed(format!("OpenPGP: {}", e)))
+}
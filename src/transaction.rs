@@ -0,0 +1,130 @@
+use crate::TarSmithError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A filesystem change made during an install, recorded so it can be undone
+#[derive(Debug)]
+enum Change {
+    /// A path that did not exist before the install
+    Created(PathBuf),
+    /// A path whose previous contents were moved aside to `backup`
+    Replaced { path: PathBuf, backup: PathBuf },
+    /// A file that was appended to; `len` is its original length
+    Appended { path: PathBuf, len: u64 },
+}
+
+/// Journal of every change an install makes
+/// Previous files are kept as backups until `commit`, and `rollback` restores them
+#[derive(Debug, Default)]
+pub struct Transaction {
+    changes: Vec<Change>,
+}
+
+/// Returns the hidden sibling path used to keep the previous version of `path`
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.tarsmith-backup", name))
+}
+
+/// Removes a file, symlink or directory without following symlinks
+fn remove_path(path: &Path) {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => {
+            fs::remove_dir_all(path).ok();
+        }
+        Ok(_) => {
+            fs::remove_file(path).ok();
+        }
+        Err(_) => {}
+    }
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves anything already at `path` aside so the caller can write a new file, symlink or directory there
+    pub fn prepare_write(&mut self, path: &Path) -> Result<(), TarSmithError> {
+        if fs::symlink_metadata(path).is_ok() {
+            let backup = backup_path(path);
+            remove_path(&backup);
+            fs::rename(path, &backup)?;
+            self.changes.push(Change::Replaced {
+                path: path.to_path_buf(),
+                backup,
+            });
+        } else {
+            self.changes.push(Change::Created(path.to_path_buf()));
+        }
+        Ok(())
+    }
+
+    /// Swaps a fully staged directory into `target`, keeping the previous tree until commit
+    pub fn install_tree(&mut self, staged: &Path, target: &Path) -> Result<(), TarSmithError> {
+        self.prepare_write(target)?;
+        fs::rename(staged, target)?;
+        Ok(())
+    }
+
+    /// Renames a staged symlink over `link` in one step, so `link` never goes missing in between;
+    /// an existing symlink is copied to the backup instead of being moved aside
+    pub fn swap_symlink(&mut self, staged: &Path, link: &Path) -> Result<(), TarSmithError> {
+        match fs::read_link(link) {
+            #[cfg(unix)]
+            Ok(target) => {
+                let backup = backup_path(link);
+                remove_path(&backup);
+                std::os::unix::fs::symlink(target, &backup)?;
+                self.changes.push(Change::Replaced {
+                    path: link.to_path_buf(),
+                    backup,
+                });
+            }
+            _ => self.prepare_write(link)?,
+        }
+        fs::rename(staged, link)?;
+        Ok(())
+    }
+
+    /// Records the current length of a file about to be appended to
+    pub fn prepare_append(&mut self, path: &Path) -> Result<(), TarSmithError> {
+        match fs::metadata(path) {
+            Ok(metadata) => self.changes.push(Change::Appended {
+                path: path.to_path_buf(),
+                len: metadata.len(),
+            }),
+            Err(_) => self.changes.push(Change::Created(path.to_path_buf())),
+        }
+        Ok(())
+    }
+
+    /// Keeps every change and deletes the backups of replaced paths
+    pub fn commit(self) {
+        for change in self.changes {
+            if let Change::Replaced { backup, .. } = change {
+                remove_path(&backup);
+            }
+        }
+    }
+
+    /// Undoes every change in reverse order, restoring replaced paths from their backups
+    pub fn rollback(self) {
+        for change in self.changes.into_iter().rev() {
+            match change {
+                Change::Created(path) => remove_path(&path),
+                Change::Replaced { path, backup } => {
+                    remove_path(&path);
+                    if fs::rename(&backup, &path).is_ok() {
+                        println!("    Restored: {}", path.display());
+                    }
+                }
+                Change::Appended { path, len } => {
+                    if let Ok(file) = fs::OpenOptions::new().write(true).open(&path) {
+                        file.set_len(len).ok();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::TarSmithError;
+use crate::elf::{self, ElfInfo};
+use crate::extract::{self, ListedEntry, ListedKind};
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Directories of bundled components, dependencies and documentation, never searched for the
+/// app's own executables, icons or desktop entries
+const BUNDLED_DIRS: &[&str] = &[
+    "node_modules",
+    "include",
+    "locales",
+    "doc",
+    "docs",
+    "man",
+    "plugins",
+    "jbr",
+    "jre",
+    "licenses",
+];
+
+/// Which part of an app tree a search looks at
+pub struct Scope {
+    /// How many directories deep below the app root files are looked for
+    max_depth: usize,
+    /// Directories skipped on top of `BUNDLED_DIRS`
+    skipped_dirs: &'static [&'static str],
+}
+
+/// Executables live at the root or in `bin/`-like directories, not among libraries or data
+pub const EXECUTABLES: Scope = Scope {
+    max_depth: 3,
+    skipped_dirs: &[
+        "lib",
+        "lib64",
+        "libexec",
+        "share",
+        "resources",
+        "swiftshader",
+    ],
+};
+
+/// Icons, down to `share/icons/hicolor/48x48/apps/`
+pub const ICONS: Scope = Scope {
+    max_depth: 6,
+    skipped_dirs: &[],
+};
+
+/// Bundled `.desktop` files, e.g. in `share/applications/`
+pub const DESKTOP_FILES: Scope = Scope {
+    max_depth: 4,
+    skipped_dirs: &[],
+};
+
+impl Scope {
+    fn skips(&self, dir: &str) -> bool {
+        let dir = dir.to_ascii_lowercase();
+        BUNDLED_DIRS.contains(&dir.as_str()) || self.skipped_dirs.contains(&dir.as_str())
+    }
+}
+
+/// An app's files, either extracted to disk or still in an archive listing
+/// Searches go through this so a dry run finds exactly what the install will
+#[derive(Clone, Copy)]
+pub enum AppTree<'a> {
+    /// An extracted app root
+    Disk(&'a Path),
+    /// An archive listing; `prefix` is the archive directory that becomes the app root, and
+    /// `root` the path it would be installed at
+    Listing {
+        entries: &'a [ListedEntry],
+        prefix: &'a Path,
+        root: &'a Path,
+    },
+}
+
+/// A regular file of an app tree, reached directly or through a symlink
+pub struct TreeFile<'a> {
+    /// Path in the installed tree
+    pub path: PathBuf,
+    /// Path below the app root
+    pub relative: PathBuf,
+    /// Number of directories between the app root and the file
+    pub depth: usize,
+    pub mode: u32,
+    pub size: u64,
+    listed: Option<&'a ListedEntry>,
+}
+
+impl TreeFile<'_> {
+    pub fn name(&self) -> String {
+        self.relative
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Whether the file sits directly in a `bin/` directory
+    pub fn in_bin(&self) -> bool {
+        self.relative
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|dir| dir == "bin")
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.mode & 0o111 != 0
+    }
+
+    /// The file's first bytes, or all of a `.desktop` file, as much as an archive listing keeps
+    pub fn header(&self) -> Cow<'_, [u8]> {
+        match self.listed {
+            Some(entry) => Cow::Borrowed(&entry.header),
+            None => Cow::Owned(read_header(&self.path)),
+        }
+    }
+}
+
+impl<'a> AppTree<'a> {
+    /// Where the app's files are, or would be, installed
+    pub fn root(&self) -> &'a Path {
+        match self {
+            AppTree::Disk(root) => root,
+            AppTree::Listing { root, .. } => root,
+        }
+    }
+
+    /// Every regular file within `scope`, following file symlinks but not directory symlinks
+    pub fn files(&self, scope: &Scope) -> Result<Vec<TreeFile<'a>>, TarSmithError> {
+        match *self {
+            AppTree::Disk(root) => disk_files(root, scope),
+            AppTree::Listing {
+                entries,
+                prefix,
+                root,
+            } => Ok(listed_files(entries, prefix, root, scope)),
+        }
+    }
+
+    /// Whether anything exists at `relative` below the app root
+    pub fn exists(&self, relative: &Path) -> bool {
+        match self {
+            AppTree::Disk(root) => root.join(relative).exists(),
+            AppTree::Listing {
+                entries, prefix, ..
+            } => entries.iter().any(|e| e.path == prefix.join(relative)),
+        }
+    }
+
+    /// The listed entry an installed path would be extracted from, with symlinks followed
+    pub fn listed(&self, path: &Path) -> Option<&'a ListedEntry> {
+        let AppTree::Listing {
+            entries,
+            prefix,
+            root,
+        } = *self
+        else {
+            return None;
+        };
+        let relative = path.strip_prefix(root).ok()?;
+        let entry = entries.iter().find(|e| e.path == prefix.join(relative))?;
+        resolve_listed(entries, entry)
+    }
+
+    /// The first bytes of an installed file, as `TreeFile::header`; empty if it cannot be read
+    pub fn header(&self, path: &Path) -> Cow<'a, [u8]> {
+        match self {
+            AppTree::Disk(_) => Cow::Owned(read_header(path)),
+            AppTree::Listing { .. } => self
+                .listed(path)
+                .map(|entry| Cow::Borrowed(entry.header.as_slice()))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The ELF header of an executable; a listing does not keep the program interpreter
+    pub fn elf_info(&self, exe: &Path) -> Option<ElfInfo> {
+        match self {
+            AppTree::Disk(_) => elf::inspect(exe),
+            AppTree::Listing { .. } => elf::inspect_header(&self.header(exe)),
+        }
+    }
+}
+
+/// Reads as much of a file as an archive listing keeps of it
+fn read_header(path: &Path) -> Vec<u8> {
+    let mut header = Vec::new();
+    File::open(path)
+        .and_then(|file| {
+            file.take(extract::listed_len(path))
+                .read_to_end(&mut header)
+        })
+        .ok();
+    header
+}
+
+fn disk_files<'a>(root: &Path, scope: &Scope) -> Result<Vec<TreeFile<'a>>, TarSmithError> {
+    let mut files = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0usize)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                if depth < scope.max_depth && !scope.skips(&entry.file_name().to_string_lossy()) {
+                    pending.push((path, depth + 1));
+                }
+                continue;
+            }
+
+            // Follows file symlinks such as node's `bin/npm -> ../lib/node_modules/npm/bin/npm-cli.js`
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            #[cfg(unix)]
+            let mode = {
+                use std::os::unix::fs::PermissionsExt;
+                metadata.permissions().mode()
+            };
+            #[cfg(not(unix))]
+            let mode = 0o755;
+
+            files.push(TreeFile {
+                relative: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                path,
+                depth,
+                mode,
+                size: metadata.len(),
+                listed: None,
+            });
+        }
+    }
+
+    Ok(files)
+}
+
+fn listed_files<'a>(
+    entries: &'a [ListedEntry],
+    prefix: &Path,
+    root: &Path,
+    scope: &Scope,
+) -> Vec<TreeFile<'a>> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let relative = entry.path.strip_prefix(prefix).ok()?;
+            let dirs: Vec<_> = relative.parent()?.iter().collect();
+            if dirs.len() > scope.max_depth
+                || dirs.iter().any(|d| scope.skips(&d.to_string_lossy()))
+            {
+                return None;
+            }
+            let file = resolve_listed(entries, entry)?;
+            if file.kind != ListedKind::File {
+                return None;
+            }
+            Some(TreeFile {
+                path: root.join(relative),
+                relative: relative.to_path_buf(),
+                depth: dirs.len(),
+                mode: file.mode,
+                size: file.size,
+                listed: Some(file),
+            })
+        })
+        .collect()
+}
+
+/// Follows a listed entry's symlinks (up to a few hops) to the entry they point at
+fn resolve_listed<'a>(
+    entries: &'a [ListedEntry],
+    entry: &'a ListedEntry,
+) -> Option<&'a ListedEntry> {
+    let mut current = entry;
+    for _ in 0..8 {
+        let ListedKind::Symlink(target) = &current.kind else {
+            return Some(current);
+        };
+        let mut resolved: Vec<Component> = current
+            .path
+            .parent()
+            .map(|parent| parent.components().collect())
+            .unwrap_or_default();
+        for component in target.components() {
+            match component {
+                Component::ParentDir => {
+                    resolved.pop()?;
+                }
+                Component::Normal(_) => resolved.push(component),
+                _ => {}
+            }
+        }
+        let resolved: PathBuf = resolved.iter().collect();
+        current = entries.iter().find(|e| e.path == resolved)?;
+    }
+    None
+}
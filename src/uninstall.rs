@@ -0,0 +1,155 @@
+use crate::caches;
+use crate::icons;
+use crate::launcher;
+use crate::registry::{self, InstallRecord};
+use crate::{PATH_EXPORT_MARKER, TarSmithError, check_sudo_permissions};
+use std::fs;
+use std::path::Path;
+
+/// Removes an installed app using its install record
+/// Only symlinks that still point into the app's install directory are deleted
+pub fn uninstall(app_name: &str, is_user_level: bool) -> Result<(), TarSmithError> {
+    println!("=== TarSmith Uninstaller ===");
+
+    if !is_user_level && !check_sudo_permissions() {
+        eprintln!("Error: System-wide uninstallation requires sudo privileges.");
+        eprintln!(
+            "Please run with: sudo tarsmith uninstall {} --system",
+            app_name
+        );
+        std::process::exit(1);
+    }
+
+    let record = registry::load_record(app_name, is_user_level)?;
+    println!("[1] Found install record for {} ✔", record.app_name);
+
+    println!("[2] Removing PATH symlinks...");
+    for symlink_path in &record.symlinks {
+        remove_owned_symlink(symlink_path, &record.install_dir)?;
+    }
+
+    if let Some(desktop_file) = &record.desktop_file {
+        if desktop_file.exists() {
+            fs::remove_file(desktop_file)?;
+            println!("[3] Removed desktop entry: {} ✔", desktop_file.display());
+        } else {
+            println!(
+                "[3] Desktop entry already gone: {} ✔",
+                desktop_file.display()
+            );
+        }
+    } else {
+        println!("[3] No desktop entry to remove ✔");
+    }
+    for icon in &record.icons {
+        if icon.exists() {
+            fs::remove_file(icon)?;
+            println!("    Removed icon: {}", icon.display());
+        }
+    }
+    let theme_dir = icons::theme_dir(record.is_user_level)?;
+    caches::refresh(
+        record.desktop_file.as_deref().and_then(Path::parent),
+        (!record.icons.is_empty()).then_some(theme_dir.as_path()),
+    );
+
+    if record.install_dir.exists() {
+        fs::remove_dir_all(&record.install_dir)?;
+        println!(
+            "[4] Removed install directory: {} ✔",
+            record.install_dir.display()
+        );
+    } else {
+        println!(
+            "[4] Install directory already gone: {} ✔",
+            record.install_dir.display()
+        );
+    }
+
+    registry::remove_record(&record.app_name, is_user_level)?;
+
+    if let Some(config_file) = &record.shell_rc {
+        remove_path_export_if_unused(config_file, &record)?;
+    }
+
+    println!(
+        "
+Uninstall complete! {} removed.",
+        record.app_name
+    );
+
+    Ok(())
+}
+
+/// Deletes a symlink or wrapper launcher only if it still launches something in the app's install directory
+fn remove_owned_symlink(symlink_path: &Path, install_dir: &Path) -> Result<(), TarSmithError> {
+    let kind = if launcher::is_wrapper(symlink_path) {
+        "wrapper"
+    } else {
+        "symlink"
+    };
+    match launcher::target(symlink_path) {
+        Some(target) if target.starts_with(install_dir) => {
+            fs::remove_file(symlink_path)?;
+            println!("    Removed {}: {}", kind, symlink_path.display());
+        }
+        Some(target) => {
+            println!(
+                "    Kept {}: {} (now points to {})",
+                kind,
+                symlink_path.display(),
+                target.display()
+            );
+        }
+        None if symlink_path.exists() => {
+            println!(
+                "    Kept {}: no longer a symlink or TarSmith wrapper",
+                symlink_path.display()
+            );
+        }
+        None => {
+            println!("    Symlink already gone: {}", symlink_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Removes the PATH export TarSmith appended to a shell config file,
+/// unless another installed app still relies on it
+fn remove_path_export_if_unused(
+    config_file: &Path,
+    record: &InstallRecord,
+) -> Result<(), TarSmithError> {
+    // Apps installed after the export was added rely on it without recording it
+    let still_used = registry::load_records(record.is_user_level)?
+        .iter()
+        .any(|other| other.shell_rc.as_deref() == Some(config_file) || !other.symlinks.is_empty());
+    if still_used || !config_file.exists() {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(config_file)?;
+    let mut kept = Vec::new();
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        if line == PATH_EXPORT_MARKER {
+            lines.next();
+            continue;
+        }
+        kept.push(line);
+    }
+
+    let mut updated = kept.join("\n");
+    if contents.ends_with('\n') && !updated.is_empty() {
+        updated.push('\n');
+    }
+    if updated != contents {
+        fs::write(config_file, updated)?;
+        println!(
+            "[5] Removed TarSmith PATH export from {} ✔",
+            config_file.display()
+        );
+    }
+
+    Ok(())
+}
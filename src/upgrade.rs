@@ -0,0 +1,172 @@
+use crate::archive_name::{self, Version};
+use crate::discover;
+use crate::launcher;
+use crate::registry::{self, InstallRecord};
+use crate::transaction::Transaction;
+use crate::{Args, TarSmithError, install};
+use std::path::{Path, PathBuf};
+
+/// Installs a newer archive of an already installed app, reusing the recorded
+/// install type, desktop executable and PATH selection
+pub fn upgrade(
+    app_name: &str,
+    archive: &Path,
+    is_user_level: bool,
+    allow_downgrade: bool,
+    args: &Args,
+) -> Result<(), TarSmithError> {
+    let record = registry::load_record(app_name, is_user_level)?;
+
+    let parsed = archive_name::parse(
+        &archive
+            .file_name()
+            .ok_or(TarSmithError::CannotFindArchiveName)?
+            .to_string_lossy(),
+    );
+    if discover::normalize(&parsed.name) != discover::normalize(&record.app_name) {
+        return Err(TarSmithError::ArchiveAppMismatch {
+            app: record.app_name,
+            found: parsed.name,
+            archive: archive.to_path_buf(),
+        });
+    }
+
+    let new_version = parsed
+        .version
+        .as_ref()
+        .map(Version::to_string)
+        .unwrap_or_else(|| "unknown".to_string());
+    let is_newer = match (Version::parse(&record.version), &parsed.version) {
+        (Some(installed), Some(new)) => *new > installed,
+        (None, Some(_)) => true,
+        (_, None) => false,
+    };
+    if !is_newer && !allow_downgrade {
+        return Err(TarSmithError::NotNewerVersion {
+            installed: record.version,
+            new: new_version,
+        });
+    }
+
+    println!(
+        "Upgrading {} from {} to {}",
+        record.app_name,
+        if record.version.is_empty() {
+            "unknown"
+        } else {
+            &record.version
+        },
+        new_version
+    );
+    install(archive, args, Some(&record))
+}
+
+/// A file of the previous install that the new one no longer creates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Retired {
+    Symlink(PathBuf),
+    DesktopEntry(PathBuf),
+    Icon(PathBuf),
+}
+
+impl Retired {
+    pub fn path(&self) -> &Path {
+        match self {
+            Retired::Symlink(path) | Retired::DesktopEntry(path) | Retired::Icon(path) => path,
+        }
+    }
+}
+
+/// Lists the PATH symlinks, desktop entry and icons of the previous install that the new one,
+/// creating `symlinks`, `desktop_file` and `icons`, leaves behind, e.g. for executables that
+/// disappeared; symlinks that no longer point into the app are not its to remove
+pub fn retired(
+    previous: &InstallRecord,
+    symlinks: &[PathBuf],
+    desktop_file: Option<&Path>,
+    icons: &[PathBuf],
+) -> Vec<Retired> {
+    let mut retired: Vec<Retired> = previous
+        .symlinks
+        .iter()
+        .filter(|link| {
+            !symlinks.contains(link)
+                && launcher::target(link).is_some_and(|t| t.starts_with(&previous.install_dir))
+        })
+        .cloned()
+        .map(Retired::Symlink)
+        .collect();
+
+    if let Some(old_desktop) = &previous.desktop_file
+        && desktop_file != Some(old_desktop.as_path())
+        && old_desktop.exists()
+    {
+        retired.push(Retired::DesktopEntry(old_desktop.clone()));
+    }
+
+    retired.extend(
+        previous
+            .icons
+            .iter()
+            .filter(|icon| !icons.contains(icon) && icon.exists())
+            .cloned()
+            .map(Retired::Icon),
+    );
+    retired
+}
+
+/// Removes what `retired` lists; the files are restored if the install rolls back
+pub fn retire_previous(
+    previous: &InstallRecord,
+    symlinks: &[PathBuf],
+    desktop_file: Option<&Path>,
+    icons: &[PathBuf],
+    transaction: &mut Transaction,
+) -> Result<(), TarSmithError> {
+    for retired in retired(previous, symlinks, desktop_file, icons) {
+        transaction.prepare_write(retired.path())?;
+        match &retired {
+            Retired::Symlink(link) => println!(
+                "    Removed symlink: {} (no longer shipped)",
+                link.display()
+            ),
+            Retired::DesktopEntry(path) => {
+                println!("    Removed desktop entry: {}", path.display())
+            }
+            Retired::Icon(path) => println!("    Removed icon: {}", path.display()),
+        }
+    }
+    Ok(())
+}
+
+/// Prints which executables appeared or disappeared between the previous and the new version
+pub fn report_executable_changes(previous: &[String], current: &[String]) {
+    if previous.is_empty() {
+        return;
+    }
+
+    let added: Vec<&str> = current
+        .iter()
+        .filter(|name| !previous.contains(name))
+        .map(String::as_str)
+        .collect();
+    let removed: Vec<&str> = previous
+        .iter()
+        .filter(|name| !current.contains(name))
+        .map(String::as_str)
+        .collect();
+
+    if added.is_empty() && removed.is_empty() {
+        println!("[7] Executables unchanged since the previous version ✔");
+        return;
+    }
+    if !added.is_empty() {
+        println!(
+            "[7] New executables (not added to PATH): {}",
+            added.join(", ")
+        );
+    }
+    if !removed.is_empty() {
+        println!("[7] Executables no longer shipped: {}", removed.join(", "));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::archive_name;
+use crate::registry;
+use crate::transaction::Transaction;
+use crate::{TarSmithError, check_sudo_permissions, infer_app_name};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the symlink inside an app directory that points at the active version
+pub const CURRENT_LINK: &str = "current";
+
+/// Version directory used when neither the folder nor the archive name carries a version
+const UNVERSIONED: &str = "unversioned";
+
+/// Installs a staged tree as one version of an app under `<install_dir>/<app>/<version>/`,
+/// then atomically points `<install_dir>/<app>/current` at it; `known_name` replaces the app name
+/// inferred from the folder, for upgrades of an app the archive spells differently
+/// An existing app directory that no install record owns is only replaced with `force`
+/// Returns the `current` path (through which executables are referenced), the app name and the version
+pub fn install_versioned(
+    staged: &Path,
+    folder_path: &Path,
+    archive: &Path,
+    known_name: Option<&str>,
+    is_user_level: bool,
+    force: bool,
+    transaction: &mut Transaction,
+) -> Result<(PathBuf, String, String), TarSmithError> {
+    let install_dir = folder_path
+        .parent()
+        .ok_or(TarSmithError::CannotGetDirectoryName)?;
+    let app_name = match known_name {
+        Some(name) => name.to_string(),
+        None => infer_app_name(folder_path)?,
+    };
+
+    let folder_name = folder_path
+        .file_name()
+        .ok_or(TarSmithError::CannotGetFolderName)?
+        .to_string_lossy();
+    let version = version_of(&folder_name, archive)?;
+
+    let app_dir = install_dir.join(&app_name);
+    let current = app_dir.join(CURRENT_LINK);
+
+    if !current.is_symlink() {
+        check_app_dir(&app_dir, &app_name, is_user_level, force)?;
+        // Fresh app, or a flat install from before versioned layouts: start a clean app directory
+        transaction.prepare_write(&app_dir)?;
+        fs::create_dir_all(&app_dir)?;
+    }
+
+    transaction.install_tree(staged, &app_dir.join(&version))?;
+
+    transaction.swap_symlink(&stage_current(&app_dir, &version)?, &current)?;
+
+    Ok((current, app_name, version))
+}
+
+/// Refuses to replace an app directory without a `current` link that no install record owns,
+/// such as `/opt/go` unpacked by hand, unless `force` is given
+pub fn check_app_dir(
+    app_dir: &Path,
+    app_name: &str,
+    is_user_level: bool,
+    force: bool,
+) -> Result<(), TarSmithError> {
+    let foreign = app_dir.exists()
+        && !app_dir.join(CURRENT_LINK).is_symlink()
+        && registry::load_record(app_name, is_user_level).is_err();
+    if foreign && !force {
+        return Err(TarSmithError::ForeignAppDirectory(app_dir.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Creates a symlink to `version` next to `current`, ready to be renamed over it so the link is
+/// replaced atomically
+fn stage_current(app_dir: &Path, version: &str) -> Result<PathBuf, TarSmithError> {
+    let staged_link = app_dir.join(format!(".{}.tarsmith-new", CURRENT_LINK));
+    fs::remove_file(&staged_link).ok();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(version, &staged_link)?;
+    Ok(staged_link)
+}
+
+/// Picks the version directory name from the extracted folder's name, then the archive's
+pub fn version_of(folder_name: &str, archive: &Path) -> Result<String, TarSmithError> {
+    let archive_name = archive
+        .file_name()
+        .ok_or(TarSmithError::CannotFindArchiveName)?
+        .to_string_lossy();
+    Ok(archive_name::parse(folder_name)
+        .version
+        .or_else(|| archive_name::parse(&archive_name).version)
+        .map(|version| version.to_string())
+        .unwrap_or_else(|| UNVERSIONED.to_string()))
+}
+
+/// Lists the version directories installed for an app, sorted by name
+pub fn installed_versions(app_dir: &Path) -> Result<Vec<String>, TarSmithError> {
+    let mut versions = Vec::new();
+    if app_dir.is_dir() {
+        for entry in fs::read_dir(app_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name != CURRENT_LINK && !name.starts_with('.') && entry.file_type()?.is_dir() {
+                versions.push(name);
+            }
+        }
+    }
+    versions.sort();
+    Ok(versions)
+}
+
+/// Points an app's `current` symlink at another installed version without re-extracting
+/// The link is replaced atomically by renaming a freshly created one over it
+pub fn switch(app_name: &str, version: &str, is_user_level: bool) -> Result<(), TarSmithError> {
+    if !is_user_level && !check_sudo_permissions() {
+        eprintln!("Error: Switching a system-wide app requires sudo privileges.");
+        eprintln!(
+            "Please run with: sudo tarsmith switch {} {} --system",
+            app_name, version
+        );
+        std::process::exit(1);
+    }
+
+    let mut record = registry::load_record(app_name, is_user_level)?;
+    let app_dir = &record.install_dir;
+
+    let versions = installed_versions(app_dir)?;
+    if !versions.iter().any(|v| v == version) {
+        return Err(TarSmithError::VersionNotInstalled {
+            app: app_name.to_string(),
+            version: version.to_string(),
+            available: versions.join(", "),
+        });
+    }
+
+    let staged_link = stage_current(app_dir, version)?;
+    fs::rename(&staged_link, app_dir.join(CURRENT_LINK))?;
+
+    record.version = version.to_string();
+    registry::save_record(&record)?;
+
+    println!("Switched {} to version {} ✔", app_name, version);
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// tests/archive_name.rs
+
+mod common;
+
+use common::{build_archive, script, tarsmith};
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Writes an archive named `archive_name` holding `<folder>/bin/<exe>`, installs it,
+/// and returns the install output
+fn install(archive_name: &str, folder: &str, exe: &str) -> (TempDir, String) {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    let archive = build_archive(
+        tar_dir.path(),
+        archive_name,
+        &[script(format!("{}/bin/{}", folder, exe), "echo ok")],
+    );
+
+    let mut cmd = tarsmith(temp_home.path());
+    cmd.arg(&archive)
+        .args(["--user", "--no-desktop", "--no-path"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    (temp_home, String::from_utf8_lossy(&output).to_string())
+}
+
+fn assert_installed(home: &Path, app: &str, version: &str, exe: &str) {
+    let app_dir = home.join(".local/tarsmith").join(app);
+    assert!(
+        app_dir.join(version).join("bin").join(exe).exists(),
+        "{} {} not installed",
+        app,
+        version
+    );
+    assert_eq!(
+        fs::read_link(app_dir.join("current")).unwrap(),
+        Path::new(version)
+    );
+}
+
+#[test]
+fn test_node_style_name() {
+    let (home, stdout) = install(
+        "node-v24.11.1-linux-x64.tar.xz",
+        "node-v24.11.1-linux-x64",
+        "node",
+    );
+    assert_installed(home.path(), "node", "24.11.1", "node");
+    assert!(stdout.contains("Built for: linux x64"));
+}
+
+#[test]
+fn test_os_and_arch_before_version() {
+    let (home, stdout) = install(
+        "zig-linux-x86_64-0.13.0.tar.xz",
+        "zig-linux-x86_64-0.13.0",
+        "zig",
+    );
+    assert_installed(home.path(), "zig", "0.13.0", "zig");
+    assert!(stdout.contains("Built for: linux x86_64"));
+    assert!(
+        !home
+            .path()
+            .join(".local/tarsmith/zig-linux-x86_64")
+            .exists()
+    );
+}
+
+#[test]
+fn test_underscore_separated_name() {
+    // 1. JDK: version from the folder, packaging suffix ignored
+    let (home, _) = install("jdk-21.0.2_linux-x64_bin.tar.gz", "jdk-21.0.2", "java");
+    assert_installed(home.path(), "jdk", "21.0.2", "java");
+
+    // 2. Telegram: unversioned folder, version from the archive name
+    let (home, _) = install("Telegram_4.16.tar.xz", "Telegram", "Telegram");
+    assert_installed(home.path(), "Telegram", "4.16", "Telegram");
+}
+
+#[test]
+fn test_target_triple_and_pre_release() {
+    let (home, stdout) = install(
+        "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+        "ripgrep-14.1.0-x86_64-unknown-linux-musl",
+        "rg",
+    );
+    assert_installed(home.path(), "ripgrep", "14.1.0", "rg");
+    assert!(stdout.contains("Built for: linux x86_64 musl"));
+
+    let (home, _) = install("app-1.2.0-rc1.tar", "app-1.2.0-rc1", "app");
+    assert_installed(home.path(), "app", "1.2.0-rc1", "app");
+}
+
+#[test]
+fn test_separator_only_names() {
+    // 1. A folder named only by a separator keeps that name
+    let (home, _) = install("_.tar", "_", "tool");
+    assert!(
+        home.path()
+            .join(".local/tarsmith/_/current/bin/tool")
+            .exists()
+    );
+
+    // 2. So does a flat archive
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let root = temp_home.path().join(".local/tarsmith");
+    let flat = |name: &str| build_archive(tar_dir.path(), name, &[script("tool", "echo ok")]);
+    tarsmith(temp_home.path())
+        .arg(flat("-.tar.gz"))
+        .args(["--user", "--no-desktop", "--no-path"])
+        .assert()
+        .success();
+    assert!(root.join("-/current/tool").exists());
+
+    // 3. An archive without any name is refused before the install root is touched
+    tarsmith(temp_home.path())
+        .arg(flat(".tar"))
+        .args(["--user", "--no-desktop", "--no-path"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("InvalidAppName"));
+    assert!(root.join("-/current/tool").exists());
+    assert!(!root.join(".tarsmith_temp_extract").exists());
+}
@@ -0,0 +1,125 @@
+// tests/checksum.rs
+
+mod common;
+
+use assert_cmd::Command;
+use common::{build_archive, script, tarsmith};
+use predicates::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Hex SHA-256 of the file at `path`
+fn sha256(path: &Path) -> String {
+    Sha256::digest(fs::read(path).expect("read archive"))
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn installer(home: &Path, archive: &Path) -> Command {
+    let mut cmd = tarsmith(home);
+    cmd.arg(archive)
+        .arg("--user")
+        .arg("--no-desktop")
+        .arg("--no-path");
+    cmd
+}
+
+#[test]
+fn test_sha256_flag_verifies() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+    let digest = sha256(&archive);
+
+    installer(temp_home.path(), &archive)
+        .arg("--sha256")
+        .arg(digest.to_uppercase())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SHA-256 verified"));
+
+    let manifest = fs::read_to_string(temp_home.path().join(".local/share/tarsmith/tool.json"))
+        .expect("manifest written");
+    assert!(manifest.contains(&digest), "digest not recorded");
+    assert!(manifest.contains("\"sha256_verified\": true"));
+}
+
+#[test]
+fn test_sha256_mismatch_aborts_before_extraction() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+
+    installer(temp_home.path(), &archive)
+        .arg("--sha256")
+        .arg("0".repeat(64))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Extracting archive").not())
+        .stderr(predicate::str::contains("ChecksumMismatch"));
+
+    assert!(!temp_home.path().join(".local/tarsmith").exists());
+}
+
+#[test]
+fn test_checksum_file_lookup() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+    let digest = sha256(&archive);
+
+    let sums = tar_dir.path().join("SHA256SUMS");
+    fs::write(
+        &sums,
+        format!(
+            "{}  other-1.0.tar.gz\n{} *tool-1.0.tar\n",
+            "a".repeat(64),
+            digest
+        ),
+    )
+    .expect("write sums");
+
+    installer(temp_home.path(), &archive)
+        .arg("--checksum-file")
+        .arg(&sums)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SHA-256 verified"));
+}
+
+#[test]
+fn test_checksum_file_missing_entry() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+
+    let sums = tar_dir.path().join("SHA256SUMS");
+    fs::write(&sums, format!("{}  other-1.0.tar.gz\n", "a".repeat(64))).expect("write sums");
+
+    installer(temp_home.path(), &archive)
+        .arg("--checksum-file")
+        .arg(&sums)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ChecksumNotFound"));
+}
@@ -0,0 +1,190 @@
+// tests/common/mod.rs
+// Helpers shared by the integration tests; each test crate uses only some of them
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Runs tarsmith with `home` as HOME and only `~/.local/bin` on PATH, so commands and
+/// libraries of the machine running the tests never affect conflict or dependency checks
+pub fn tarsmith(home: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", home)
+        .env("PATH", home.join(".local/bin"))
+        .env("SHELL", "/bin/bash")
+        .env_remove("LD_LIBRARY_PATH");
+    cmd
+}
+
+/// An entry of a test archive
+pub enum Entry {
+    /// A regular file with its contents and mode
+    File(String, Vec<u8>, u32),
+    /// A symlink and its target
+    Symlink(String, String),
+}
+
+/// A regular file entry with the given mode
+pub fn file(path: impl Into<String>, contents: impl Into<Vec<u8>>, mode: u32) -> Entry {
+    Entry::File(path.into(), contents.into(), mode)
+}
+
+/// An executable file entry
+pub fn exe(path: impl Into<String>, contents: impl Into<Vec<u8>>) -> Entry {
+    file(path, contents, 0o755)
+}
+
+/// An executable shell script entry, as written by [`write_script`]
+pub fn script(path: impl Into<String>, body: &str) -> Entry {
+    exe(path, format!("#!/bin/sh\n{}\n", body))
+}
+
+/// A symlink entry pointing at `target`
+pub fn symlink(path: impl Into<String>, target: impl Into<String>) -> Entry {
+    Entry::Symlink(path.into(), target.into())
+}
+
+/// A script at `<folder>/bin/<exe>` for every executable
+pub fn bin_scripts(folder: &str, exes: &[&str]) -> Vec<Entry> {
+    exes.iter()
+        .map(|exe| {
+            script(
+                format!("{}/bin/{}", folder, exe),
+                &format!("echo {} {}", exe, folder),
+            )
+        })
+        .collect()
+}
+
+/// Serializes entries into an uncompressed tar archive
+pub fn tar_bytes(entries: &[Entry]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for entry in entries {
+        let mut header = tar::Header::new_gnu();
+        match entry {
+            Entry::File(path, contents, mode) => {
+                header.set_size(contents.len() as u64);
+                header.set_mode(*mode);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, path, contents.as_slice())
+                    .expect("append entry");
+            }
+            Entry::Symlink(path, target) => {
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_size(0);
+                header.set_mode(0o777);
+                builder
+                    .append_link(&mut header, path, target)
+                    .expect("append symlink");
+            }
+        }
+    }
+    builder.into_inner().expect("finish tar")
+}
+
+/// Writes entries to `<dir>/<file_name>` as a tar archive, gzip-compressed when the
+/// name ends in `.tar.gz` or `.tgz`
+pub fn build_archive(dir: &Path, file_name: &str, entries: &[Entry]) -> PathBuf {
+    let mut bytes = tar_bytes(entries);
+    if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &bytes).expect("compress");
+        bytes = encoder.finish().expect("finish gzip");
+    }
+    let archive = dir.join(file_name);
+    fs::write(&archive, bytes).expect("write archive");
+    archive
+}
+
+/// Writes an executable shell script
+pub fn write_script(path: &Path, body: &str) {
+    fs::write(path, format!("#!/bin/sh\n{}\n", body)).expect("write script");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).expect("set exec perms");
+    }
+}
+
+/// ELF machine number of the architecture the tests run on
+pub fn host_machine() -> u16 {
+    match std::env::consts::ARCH {
+        "aarch64" => 183,
+        "riscv64" => 243,
+        _ => 62,
+    }
+}
+
+/// A minimal little-endian ELF64 executable header for the host architecture, padded to `size` bytes
+pub fn elf(size: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; size.max(64)];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2; // 64-bit
+    bytes[5] = 1; // little-endian
+    bytes[6] = 1;
+    bytes[16..18].copy_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+    bytes[18..20].copy_from_slice(&host_machine().to_le_bytes());
+    bytes[54..56].copy_from_slice(&56u16.to_le_bytes());
+    bytes
+}
+
+/// An ELF64 executable whose dynamic section lists `needed` as DT_NEEDED entries
+pub fn elf_with_needed(needed: &[&str]) -> Vec<u8> {
+    const HEADERS_END: usize = 64 + 2 * 56;
+
+    let mut strtab = vec![0u8];
+    let mut name_offsets = Vec::new();
+    for lib in needed {
+        name_offsets.push(strtab.len() as u64);
+        strtab.extend(lib.as_bytes());
+        strtab.push(0);
+    }
+    let dynamic_offset = (HEADERS_END + strtab.len()).next_multiple_of(8);
+
+    let mut dynamic = Vec::new();
+    let mut push_entry = |tag: u64, value: u64| {
+        dynamic.extend(tag.to_le_bytes());
+        dynamic.extend(value.to_le_bytes());
+    };
+    for offset in &name_offsets {
+        push_entry(1, *offset); // DT_NEEDED
+    }
+    push_entry(5, HEADERS_END as u64); // DT_STRTAB
+    push_entry(10, strtab.len() as u64); // DT_STRSZ
+    push_entry(0, 0); // DT_NULL
+    let total = dynamic_offset + dynamic.len();
+
+    let mut bytes = elf(64);
+    bytes[32..40].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+    bytes[56..58].copy_from_slice(&2u16.to_le_bytes()); // e_phnum
+
+    let program_header = |kind: u32, offset: u64, size: u64| {
+        let mut phdr = vec![0u8; 56];
+        phdr[..4].copy_from_slice(&kind.to_le_bytes());
+        phdr[8..16].copy_from_slice(&offset.to_le_bytes());
+        phdr[16..24].copy_from_slice(&offset.to_le_bytes());
+        phdr[32..40].copy_from_slice(&size.to_le_bytes());
+        phdr
+    };
+    bytes.extend(program_header(1, 0, total as u64)); // PT_LOAD
+    bytes.extend(program_header(
+        2,
+        dynamic_offset as u64,
+        dynamic.len() as u64,
+    )); // PT_DYNAMIC
+    bytes.extend(strtab);
+    bytes.resize(dynamic_offset, 0);
+    bytes.extend(dynamic);
+    bytes
+}
+
+/// Returns the start of a PNG file with the given dimensions; enough for the size to be read
+pub fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    data.extend_from_slice(&width.to_be_bytes());
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(&[8, 6, 0, 0, 0]);
+    data
+}
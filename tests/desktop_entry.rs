@@ -1,6 +1,9 @@
 // tests/desktop_entry.rs
 
+mod common;
+
 use assert_cmd::Command;
+use common::{build_archive, file, script};
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
@@ -26,7 +29,7 @@ fn test_desktop_entry_created() {
     let tar_dir = TempDir::new().expect("tar dir");
     let archive_path = tar_dir.path().join("dummy.tar");
     let status = std::process::Command::new("tar")
-        .args(&[
+        .args([
             "-cf",
             archive_path.to_str().unwrap(),
             "-C",
@@ -41,7 +44,8 @@ fn test_desktop_entry_created() {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
     cmd.env("HOME", temp_home.path())
         .arg(&archive_path)
-        .arg("--user"); // force user-level install
+        .arg("--user") // force user-level install
+        .args(["--desktop-exec", "dummy_bin"]); // a script, so only created on request
 
     // 5. Assertions
     cmd.assert()
@@ -49,3 +53,102 @@ fn test_desktop_entry_created() {
         .stdout(predicate::str::contains("Desktop entry created"))
         .stdout(predicate::str::contains(".desktop"));
 }
+
+#[test]
+fn test_desktop_entry_quotes_awkward_paths() {
+    // 1. A home directory with a space and a percent sign
+    let temp_root = TempDir::new().expect("temp root");
+    let home = temp_root.path().join("my home 100%");
+    fs::create_dir_all(&home).expect("create home");
+
+    // 2. Archive with a single executable
+    let archive_path = build_archive(
+        temp_root.path(),
+        "mytool-1.0.tar",
+        &[script("mytool-1.0/bin/mytool", "echo hi")],
+    );
+
+    // 3. Install
+    Command::new(env!("CARGO_BIN_EXE_tarsmith"))
+        .env("HOME", &home)
+        .env("PATH", home.join(".local/bin"))
+        .arg(&archive_path)
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success();
+
+    // 4. Exec is quoted with % doubled, TryExec and Icon stay plain strings
+    let exe = home.join(".local/tarsmith/mytool/current/bin/mytool");
+    let contents = fs::read_to_string(home.join(".local/share/applications/mytool.desktop"))
+        .expect("read desktop entry");
+    let lines: Vec<&str> = contents.lines().collect();
+    assert!(
+        lines.contains(
+            &format!("Exec=\"{}\"", exe.display().to_string().replace('%', "%%")).as_str()
+        )
+    );
+    assert!(lines.contains(&format!("TryExec={}", exe.display()).as_str()));
+    assert!(lines.contains(&"Name=mytool"));
+}
+
+#[test]
+fn test_bundled_desktop_entry_reused() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Archive shipping its own launcher with a relative Exec
+    let bundled = "[Desktop Entry]\nType=Application\nName=My Tool\nName[fr]=Mon outil\nExec=mytool %U\nIcon=mytool\nCategories=Development;IDE;\nMimeType=text/x-mytool;\nStartupWMClass=MyTool\n";
+    let archive_path = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            script("mytool-1.0/bin/mytool", "echo hi"),
+            file(
+                "mytool-1.0/share/applications/com.example.MyTool.desktop",
+                bundled,
+                0o644,
+            ),
+        ],
+    );
+
+    let exe = home.join(".local/tarsmith/mytool/current/bin/mytool");
+    let exec_line = format!("Exec={} %U", exe.display());
+
+    // 2. The dry run shows the rewritten entry
+    Command::new(env!("CARGO_BIN_EXE_tarsmith"))
+        .env("HOME", home)
+        .env("PATH", home.join(".local/bin"))
+        .arg(&archive_path)
+        .args(["--dry-run", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("based on bundled"))
+        .stdout(predicate::str::contains(exec_line.as_str()));
+
+    // 3. The installed entry keeps the bundled keys and runs the installed executable
+    Command::new(env!("CARGO_BIN_EXE_tarsmith"))
+        .env("HOME", home)
+        .env("PATH", home.join(".local/bin"))
+        .arg(&archive_path)
+        .args(["--user", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Desktop entry based on bundled"));
+
+    let contents = fs::read_to_string(home.join(".local/share/applications/mytool.desktop"))
+        .expect("read desktop entry");
+    let lines: Vec<&str> = contents.lines().collect();
+    let try_exec = format!("TryExec={}", exe.display());
+    for expected in [
+        exec_line.as_str(),
+        try_exec.as_str(),
+        "Name=My Tool",
+        "Name[fr]=Mon outil",
+        "Categories=Development;IDE;",
+        "MimeType=text/x-mytool;",
+        "StartupWMClass=MyTool",
+    ] {
+        assert!(lines.contains(&expected), "missing {}", expected);
+    }
+}
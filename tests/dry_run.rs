@@ -0,0 +1,191 @@
+// tests/dry_run.rs
+
+mod common;
+
+use common::{Entry, build_archive, file, script, symlink, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+/// Entries of `mytool-<version>`: `bin/mytool`, a `bin/mt` symlink to it and a README
+fn mytool(version: &str) -> Vec<Entry> {
+    let folder = format!("mytool-{}", version);
+    vec![
+        script(format!("{}/bin/mytool", folder), "echo mytool"),
+        file(format!("{}/README", folder), "docs", 0o644),
+        symlink(format!("{}/bin/mt", folder), "mytool"),
+    ]
+}
+
+#[test]
+fn test_dry_run_prints_plan_and_writes_nothing() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let current = home.join(".local/tarsmith/mytool/current");
+
+    // 1. Plan a fresh install, with ~/.local/bin not yet on PATH
+    tarsmith(home)
+        .env("PATH", "/usr/bin:/bin")
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar.gz",
+            &mytool("1.0"),
+        ))
+        .args(["--dry-run", "--desktop-exec", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("App: mytool 1.0"))
+        .stdout(predicate::str::contains("    mytool-1.0/bin/mytool *\n"))
+        .stdout(predicate::str::contains(
+            "    mytool-1.0/bin/mt -> mytool\n",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "create   symlink  {} -> 1.0",
+            current.display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "| Exec={}",
+            current.join("bin/mytool").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "create   symlink  {} -> {}",
+            home.join(".local/bin/mt").display(),
+            current.join("bin/mt").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "create   file     {}",
+            home.join(".bashrc").display()
+        )))
+        .stdout(predicate::str::contains(
+            "| export PATH=\"$HOME/.local/bin:$PATH\"",
+        ));
+
+    // 2. Nothing was written anywhere under HOME
+    assert_eq!(fs::read_dir(home).unwrap().count(), 0);
+}
+
+#[test]
+fn test_dry_run_shows_replaced_paths() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let current = home.join(".local/tarsmith/mytool/current");
+
+    // 1. Install 1.0 for real
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar.gz",
+            &mytool("1.0"),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+
+    // 2. Planning 2.0, which drops bin/mt, reports the links it would repoint and remove
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-2.0.tar.gz",
+            &mytool("2.0")[..2],
+        ))
+        .args(["--dry-run", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "replace  symlink  {} -> 2.0 (currently -> 1.0)",
+            current.display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "replace  symlink  {}",
+            home.join(".local/bin/mytool").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "remove   symlink  {} (currently -> {})",
+            home.join(".local/bin/mt").display(),
+            current.join("bin/mt").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "replace  file     {}",
+            home.join(".local/share/tarsmith/mytool.json").display()
+        )))
+        .stdout(predicate::str::contains(".bashrc").not());
+
+    // 3. The installed version is untouched
+    assert_eq!(fs::read_link(&current).unwrap(), PathBuf::from("1.0"));
+    assert!(!home.join(".local/tarsmith/mytool/2.0").exists());
+}
+
+#[test]
+fn test_dry_run_json() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    let output = tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar.gz",
+            &mytool("1.0"),
+        ))
+        .args([
+            "--plan",
+            "--json",
+            "--path-exec",
+            "mytool",
+            "--desktop-exec",
+            "mytool",
+        ])
+        .output()
+        .expect("run tarsmith");
+    assert!(output.status.success());
+
+    // 1. The whole of stdout is one JSON document
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+    assert_eq!(plan["app_name"], "mytool");
+    assert_eq!(plan["version"], "1.0");
+    assert_eq!(plan["format"], "gzip");
+    assert_eq!(plan["path_execs"], serde_json::json!(["mytool"]));
+    assert_eq!(plan["desktop_interface"], "terminal program (script)");
+    assert_eq!(plan["contents"].as_array().unwrap().len(), 3);
+
+    // 2. Every change carries an action, a kind and a path
+    let changes = plan["changes"].as_array().unwrap();
+    let desktop = changes
+        .iter()
+        .find(|c| c["path"].as_str().unwrap().ends_with("mytool.desktop"))
+        .expect("desktop change");
+    assert_eq!(desktop["action"], "create");
+    assert!(
+        desktop["contents"]
+            .as_str()
+            .unwrap()
+            .contains("[Desktop Entry]")
+    );
+    assert!(
+        desktop["contents"]
+            .as_str()
+            .unwrap()
+            .contains("Terminal=true")
+    );
+    assert!(
+        !changes
+            .iter()
+            .any(|c| c["path"].as_str().unwrap().ends_with("/bin/mt"))
+    );
+
+    // 3. --json is only accepted with --dry-run
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar.gz",
+            &mytool("1.0"),
+        ))
+        .args(["--user", "--json"])
+        .assert()
+        .failure();
+    assert_eq!(fs::read_dir(home).unwrap().count(), 0);
+}
@@ -0,0 +1,159 @@
+// tests/elf_arch.rs
+
+mod common;
+
+use common::{build_archive, elf, exe, host_machine, tarsmith};
+use predicates::prelude::*;
+
+use tempfile::TempDir;
+
+/// A machine number that is never the host's
+fn foreign_machine() -> u16 {
+    if host_machine() == 183 { 62 } else { 183 }
+}
+
+/// Builds a minimal ELF64 executable header for `machine` with an optional PT_INTERP segment
+fn elf_bytes(machine: u16, interpreter: Option<&str>) -> Vec<u8> {
+    let mut bytes = elf(64);
+    bytes[18..20].copy_from_slice(&machine.to_le_bytes());
+
+    if let Some(interp) = interpreter {
+        bytes[32..40].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        bytes[56..58].copy_from_slice(&1u16.to_le_bytes()); // e_phnum
+        let mut phdr = vec![0u8; 56];
+        phdr[..4].copy_from_slice(&3u32.to_le_bytes()); // PT_INTERP
+        phdr[8..16].copy_from_slice(&120u64.to_le_bytes());
+        phdr[32..40].copy_from_slice(&(interp.len() as u64 + 1).to_le_bytes());
+        bytes.extend(phdr);
+        bytes.extend(interp.as_bytes());
+        bytes.push(0);
+    }
+    bytes
+}
+
+#[test]
+fn test_foreign_architecture_refused() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[exe(
+            "mytool-1.0/bin/mytool",
+            elf_bytes(foreign_machine(), None),
+        )],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ArchitectureMismatch"));
+    assert!(!temp_home.path().join(".local/tarsmith/mytool").exists());
+
+    // A dry run refuses it just the same
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--dry-run", "--no-desktop"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ArchitectureMismatch"));
+}
+
+#[test]
+fn test_other_word_size_refused() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    // The host's machine number, but an ELF class of the other word size
+    let mut bytes = elf_bytes(host_machine(), None);
+    bytes[4] = if cfg!(target_pointer_width = "64") {
+        1
+    } else {
+        2
+    };
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[exe("mytool-1.0/bin/mytool", bytes)],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--no-path"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ArchitectureMismatch"))
+        .stderr(predicate::str::contains("-bit)"));
+}
+
+#[test]
+fn test_ignore_arch_installs_with_warning() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[exe(
+            "mytool-1.0/bin/mytool",
+            elf_bytes(foreign_machine(), None),
+        )],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--ignore-arch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Warning: mytool is built for"));
+}
+
+#[test]
+fn test_missing_interpreter_warned() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[exe(
+            "mytool-1.0/bin/mytool",
+            elf_bytes(host_machine(), Some("/lib/ld-musl-tarsmith-test.so.1")),
+        )],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "needs /lib/ld-musl-tarsmith-test.so.1 (musl), which is not installed",
+        ));
+}
+
+#[test]
+fn test_menu_shows_architecture() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[exe(
+            "mytool-1.0/bin/mytool",
+            elf_bytes(host_machine(), None),
+        )],
+    );
+
+    // Interactive: user-level, then accept the PATH default
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .write_stdin("1\n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "mytool [{}, static]",
+            std::env::consts::ARCH
+        )))
+        .stdout(predicate::str::contains("matches this machine"));
+}
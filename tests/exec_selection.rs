@@ -0,0 +1,114 @@
+// tests/exec_selection.rs
+
+mod common;
+
+use common::{bin_scripts, build_archive, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+/// Launcher scripts in the Android Studio-style bin/
+const LAUNCHERS: [&str; 4] = ["studio", "studio.sh", "format.sh", "inspect.sh"];
+
+#[test]
+fn test_desktop_and_path_exec_flags() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Desktop entry for studio, PATH for studio and studio.sh only
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "android-studio-2025.1-linux.tar",
+            &bin_scripts("android-studio", &LAUNCHERS),
+        ))
+        .args([
+            "--user",
+            "--desktop-exec",
+            "studio",
+            "--path-exec",
+            "studio",
+            "--path-exec",
+            "studio.sh",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Using executable matching studio for desktop entry: studio",
+        ));
+
+    // 2. Only the requested executables were used
+    let desktop = fs::read_to_string(home.join(".local/share/applications/android-studio.desktop"))
+        .expect("desktop entry");
+    assert!(desktop.contains("/current/bin/studio\n"));
+    assert!(home.join(".local/bin/studio").is_symlink());
+    assert!(home.join(".local/bin/studio.sh").is_symlink());
+    assert!(!home.join(".local/bin/format.sh").exists());
+    assert!(!home.join(".local/bin/inspect.sh").exists());
+}
+
+#[test]
+fn test_path_exec_glob() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "android-studio-2025.1-linux.tar",
+            &bin_scripts("android-studio", &LAUNCHERS),
+        ))
+        .args(["--user", "--no-desktop", "--path-exec", "*.sh"])
+        .assert()
+        .success();
+
+    assert!(!home.join(".local/bin/studio").exists());
+    for exe in ["studio.sh", "format.sh", "inspect.sh"] {
+        assert!(home.join(".local/bin").join(exe).is_symlink(), "{}", exe);
+    }
+}
+
+#[test]
+fn test_unmatched_name_is_an_error() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Unknown desktop executable
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "android-studio-2025.1-linux.tar",
+            &bin_scripts("android-studio", &LAUNCHERS),
+        ))
+        .args(["--user", "--desktop-exec", "idea"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NoMatchingExecutable"))
+        .stderr(predicate::str::contains("idea"));
+
+    // 2. One of several PATH patterns matches nothing
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "android-studio-2025.1-linux.tar",
+            &bin_scripts("android-studio", &LAUNCHERS),
+        ))
+        .args([
+            "--user",
+            "--no-desktop",
+            "--path-exec",
+            "studio",
+            "--path-exec",
+            "studio64*",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NoMatchingExecutable"));
+
+    assert!(!home.join(".local/bin/studio").exists());
+    assert!(!home.join(".local/tarsmith/android-studio").exists());
+}
@@ -0,0 +1,184 @@
+// tests/executable_discovery.rs
+
+mod common;
+
+use common::{build_archive, elf, elf_with_needed, exe, script, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+fn desktop_exec(home: &Path, app: &str) -> String {
+    let contents = fs::read_to_string(
+        home.join(".local/share/applications")
+            .join(format!("{}.desktop", app)),
+    )
+    .expect("desktop entry");
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("Exec="))
+        .expect("Exec line")
+        .to_string()
+}
+
+#[test]
+fn test_root_binary_preferred_and_helpers_excluded() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Firefox-style layout: main binary at the root, helpers and libraries beside it;
+    //    libxul.so links GTK, which makes the binaries next to it GUI programs
+    let archive = build_archive(
+        tar_dir.path(),
+        "firefox-128.0.tar",
+        &[
+            exe("firefox/crashreporter", elf(4096)),
+            exe("firefox/firefox", elf(700 * 1024)),
+            exe("firefox/firefox-bin", elf(4096)),
+            exe("firefox/libxul.so", elf_with_needed(&["libgtk-3.so.0"])),
+            exe("firefox/pingsender", elf(4096)),
+            script("firefox/uninstall.sh", "rm -rf ."),
+            exe("firefox/gmp-clearkey/0.1/libclearkey.so", elf(4096)),
+        ],
+    );
+    tarsmith(home)
+        .arg(&archive)
+        .arg("--user")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "GUI (libxul.so next to it links against libgtk-3.so.0)",
+        ));
+
+    // 2. The best match gets the desktop entry; only real programs reach PATH
+    let app_dir = home.join(".local/tarsmith/firefox/current");
+    assert_eq!(
+        desktop_exec(home, "firefox"),
+        app_dir.join("firefox").display().to_string()
+    );
+    assert!(home.join(".local/bin/firefox").is_symlink());
+    assert!(home.join(".local/bin/firefox-bin").is_symlink());
+    for helper in ["crashreporter", "libxul.so", "pingsender", "uninstall.sh"] {
+        assert!(
+            !home.join(".local/bin").join(helper).exists(),
+            "{} offered",
+            helper
+        );
+    }
+}
+
+#[test]
+fn test_menu_ranks_matching_elf_first() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    // VS Code-style layout with the launcher script in bin/ and crash handlers at the root
+    let archive = build_archive(
+        tar_dir.path(),
+        "code-stable-x64.tar",
+        &[
+            script("VSCode-linux-x64/bin/code", "exec code"),
+            exe("VSCode-linux-x64/bin/code-tunnel", elf(4096)),
+            exe("VSCode-linux-x64/chrome_crashpad_handler", elf(4096)),
+            exe("VSCode-linux-x64/chrome-sandbox", elf(4096)),
+            exe("VSCode-linux-x64/code", elf(2 * 1024 * 1024)),
+            exe(
+                "VSCode-linux-x64/resources/app/out/cli.sh",
+                b"#!/bin/sh".to_vec(),
+            ),
+        ],
+    );
+
+    // Interactive: user-level, skip the desktop entry, accept the PATH default
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .write_stdin("1\n0\n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "    1) code [{}",
+            std::env::consts::ARCH
+        )))
+        .stdout(predicate::str::contains("    2) code-tunnel ["))
+        .stdout(predicate::str::contains("chrome_crashpad_handler").not())
+        .stdout(predicate::str::contains("chrome-sandbox").not())
+        .stdout(predicate::str::contains("cli.sh").not());
+}
+
+#[test]
+fn test_helpers_offered_when_nothing_else() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/install.sh", "echo install")],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created symlink: install.sh"));
+}
+
+#[test]
+fn test_order_is_deterministic() {
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    // 1. The same executables packed in two different orders
+    let forward = build_archive(
+        tar_dir.path(),
+        "suite-1.0.tar",
+        &[
+            script("suite-1.0/bin/alpha", "echo ok"),
+            script("suite-1.0/bin/mid", "echo ok"),
+            script("suite-1.0/bin/zeta", "echo ok"),
+        ],
+    );
+    let reversed = build_archive(
+        tar_dir.path(),
+        "suite-2.0.tar",
+        &[
+            script("suite-2.0/bin/zeta", "echo ok"),
+            script("suite-2.0/bin/mid", "echo ok"),
+            script("suite-2.0/bin/alpha", "echo ok"),
+        ],
+    );
+
+    // 2. Menus list equally ranked executables by name, with the default marked
+    for archive in [&forward, &reversed] {
+        let temp_home = TempDir::new().expect("temp home");
+        tarsmith(temp_home.path())
+            .arg(archive)
+            .write_stdin("1\n0\n\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "    1) alpha [script] [terminal] (top-ranked)\n    2) mid [script] [terminal]\n    3) zeta [script] [terminal]\n",
+            ));
+    }
+
+    // 3. Non-interactive installs judge the same top-ranked executable, and skip the desktop
+    //    entry for these terminal programs unless one is asked for
+    for archive in [&forward, &reversed] {
+        let temp_home = TempDir::new().expect("temp home");
+        tarsmith(temp_home.path())
+            .arg(archive)
+            .arg("--user")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "No GUI executable found (alpha is a terminal program (script))",
+            ));
+        assert!(
+            !temp_home
+                .path()
+                .join(".local/share/applications/suite.desktop")
+                .exists()
+        );
+    }
+}
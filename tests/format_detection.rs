@@ -0,0 +1,85 @@
+// tests/format_detection.rs
+
+mod common;
+
+use common::{script, tar_bytes, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Builds an uncompressed tar stream holding `tool-1.0/bin/tool` with the exec bit set
+fn tool_tar() -> Vec<u8> {
+    tar_bytes(&[script("tool-1.0/bin/tool", "echo tool")])
+}
+
+fn run_install(home: &Path, archive: &Path) -> assert_cmd::assert::Assert {
+    let mut cmd = tarsmith(home);
+    cmd.arg(archive)
+        .arg("--user")
+        .arg("--no-desktop")
+        .arg("--no-path");
+    cmd.assert()
+}
+
+#[test]
+fn test_gzip_without_extension() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("tar dir");
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&tool_tar()).expect("gzip");
+    let archive = dir.path().join("download");
+    fs::write(&archive, encoder.finish().unwrap()).expect("write archive");
+
+    run_install(temp_home.path(), &archive)
+        .success()
+        .stdout(predicate::str::contains(
+            "Detected format: gzip (from file contents)",
+        ));
+}
+
+#[test]
+fn test_mislabelled_tgz_is_xz() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("tar dir");
+
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&tool_tar()).expect("xz");
+    let archive = dir.path().join("tool-1.0.tgz");
+    fs::write(&archive, encoder.finish().unwrap()).expect("write archive");
+
+    run_install(temp_home.path(), &archive)
+        .success()
+        .stdout(predicate::str::contains("Detected format: xz"));
+}
+
+#[test]
+fn test_lz4_archive() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("tar dir");
+
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    encoder.write_all(&tool_tar()).expect("lz4");
+    let archive = dir.path().join("tool-1.0.tar.lz4");
+    fs::write(&archive, encoder.finish().unwrap()).expect("write archive");
+
+    run_install(temp_home.path(), &archive)
+        .success()
+        .stdout(predicate::str::contains("Detected format: lz4"));
+}
+
+#[test]
+fn test_unsupported_format_is_named() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("tar dir");
+
+    let archive = dir.path().join("tool-1.0.tar.gz");
+    fs::write(&archive, b"LZIP\x01rest-of-stream").expect("write archive");
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsupportedFormat(\"lzip\")"));
+}
@@ -0,0 +1,127 @@
+// tests/gui_detection.rs
+
+mod common;
+
+use common::{build_archive, elf, elf_with_needed, exe, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[test]
+fn test_terminal_program_skips_desktop_entry() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let entry = home.join(".local/share/applications/kubetool.desktop");
+
+    // 1. A CLI tool: a native program without GUI libraries
+    let archive = build_archive(
+        tar_dir.path(),
+        "kubetool-1.0.tar",
+        &[exe("kubetool-1.0/bin/kubetool", elf(4096))],
+    );
+
+    // 2. The non-interactive default skips the desktop entry and says why
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No GUI executable found (kubetool is a terminal program (links no GUI libraries))",
+        ));
+    assert!(!entry.exists());
+
+    // 3. Asking for one gives an entry that opens a terminal
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path", "--desktop-exec", "kubetool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is a terminal program"));
+    let contents = fs::read_to_string(&entry).expect("read desktop entry");
+    assert!(contents.lines().any(|l| l == "Terminal=true"));
+}
+
+#[test]
+fn test_lone_terminal_program_prompts_for_desktop_entry() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let entry = home.join(".local/share/applications/kubetool.desktop");
+    let archive = build_archive(
+        tar_dir.path(),
+        "kubetool-1.0.tar",
+        &[exe("kubetool-1.0/bin/kubetool", elf(4096))],
+    );
+
+    // 1. The interactive menu asks instead of skipping, and no is the default
+    tarsmith(home)
+        .arg(&archive)
+        .write_stdin("1\n\nn\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "a terminal program (links no GUI libraries)",
+        ))
+        .stdout(predicate::str::contains(
+            "Create a desktop entry that opens it in a terminal? (y/N)",
+        ))
+        .stdout(predicate::str::contains("Skipped desktop entry creation"));
+    assert!(!entry.exists());
+
+    // 2. Answering yes creates an entry that opens a terminal
+    tarsmith(home)
+        .arg(&archive)
+        .write_stdin("1\ny\nn\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is a terminal program"));
+    let contents = fs::read_to_string(&entry).expect("read desktop entry");
+    assert!(contents.lines().any(|l| l == "Terminal=true"));
+}
+
+#[test]
+fn test_gui_executable_preferred_for_desktop_entry() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. The top-ranked executable is a CLI, the GUI one links libX11
+    let archive = build_archive(
+        tar_dir.path(),
+        "viewer-1.0.tar",
+        &[
+            exe("viewer-1.0/viewer", elf(4096)),
+            exe(
+                "viewer-1.0/viewer-gui",
+                elf_with_needed(&["libc.so.6", "libX11.so.6"]),
+            ),
+        ],
+    );
+
+    // 2. The dry run cannot read linked libraries and keeps the top-ranked one
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--dry-run", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "assumed GUI (linked libraries are only read during a real install)",
+        ));
+
+    // 3. The install picks the GUI executable, in a window of its own
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Using top-ranked GUI executable for desktop entry (default): viewer-gui, GUI (links against libX11.so.6)",
+        ));
+    let contents = fs::read_to_string(home.join(".local/share/applications/viewer.desktop"))
+        .expect("read desktop entry");
+    assert!(contents.lines().any(|l| l.ends_with("/current/viewer-gui")));
+    assert!(contents.lines().any(|l| l == "Terminal=false"));
+}
@@ -0,0 +1,271 @@
+// tests/hostile_archive.rs
+
+mod common;
+
+use common::tarsmith;
+use predicates::prelude::*;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+/// Appends an entry whose name is written verbatim, bypassing the builder's own path checks
+fn append_raw(builder: &mut tar::Builder<Vec<u8>>, name: &str, data: &[u8]) {
+    let mut header = tar::Header::new_old();
+    header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+    header.set_size(data.len() as u64);
+    header.set_mode(0o755);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_cksum();
+    builder.append(&header, data).expect("append raw entry");
+}
+
+fn append_symlink(builder: &mut tar::Builder<Vec<u8>>, name: &str, target: &str) {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    header.set_mode(0o777);
+    builder
+        .append_link(&mut header, name, target)
+        .expect("append symlink");
+}
+
+fn append_file(builder: &mut tar::Builder<Vec<u8>>, name: &str) {
+    let data = b"#!/bin/sh\necho ok";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, &data[..])
+        .expect("append file");
+}
+
+fn run_install(home: &Path, archive: &Path) -> assert_cmd::assert::Assert {
+    let mut cmd = tarsmith(home);
+    cmd.arg(archive)
+        .arg("--user")
+        .arg("--no-desktop")
+        .arg("--no-path");
+    cmd.assert()
+}
+
+fn write_tar(dir: &Path, builder: tar::Builder<Vec<u8>>) -> std::path::PathBuf {
+    let archive = dir.join("evil.tar");
+    fs::write(&archive, builder.into_inner().expect("finish tar")).expect("write tar");
+    archive
+}
+
+#[test]
+fn test_parent_dir_entry_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "app/bin/app");
+    append_raw(&mut builder, "../escaped.sh", b"#!/bin/sh\necho pwned");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeArchivePath"));
+
+    assert!(!temp_home.path().join(".local/tarsmith/escaped.sh").exists());
+    assert!(
+        !temp_home
+            .path()
+            .join(".local/tarsmith/.tarsmith_temp_extract")
+            .exists(),
+        "temp extraction dir left behind"
+    );
+}
+
+#[test]
+fn test_absolute_entry_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let outside = TempDir::new().expect("outside dir");
+    let target = outside.path().join("absolute.sh");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    append_raw(&mut builder, target.to_str().unwrap(), b"pwned");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeArchivePath"));
+
+    assert!(!target.exists(), "absolute entry was written");
+}
+
+#[test]
+fn test_escaping_symlink_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "app/bin/app");
+    append_symlink(&mut builder, "app/link", "../../outside");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeSymlink"));
+}
+
+#[test]
+fn test_write_through_absolute_symlink_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let outside = TempDir::new().expect("outside dir");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    append_symlink(&mut builder, "app/link", outside.path().to_str().unwrap());
+    append_file(&mut builder, "app/link/payload");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeSymlink"));
+
+    assert!(
+        !outside.path().join("payload").exists(),
+        "wrote through symlink"
+    );
+}
+
+#[test]
+fn test_chained_symlink_escape_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    // `sub/d` points back at `app`, so `sub/d/../../..` climbs out of the root
+    // even though each link looks harmless on its own
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "app/bin/app");
+    append_symlink(&mut builder, "app/sub/d", "..");
+    append_symlink(&mut builder, "app/e", "sub/d/../../..");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeSymlink"));
+}
+
+#[test]
+fn test_zip_parent_dir_entry_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let zip_dir = TempDir::new().expect("zip dir");
+
+    let archive = zip_dir.path().join("evil.zip");
+    let mut writer = zip::ZipWriter::new(File::create(&archive).expect("create zip"));
+    writer
+        .start_file("../escaped.sh", SimpleFileOptions::default())
+        .expect("start entry");
+    writer.write_all(b"pwned").expect("write entry");
+    writer.finish().expect("finish zip");
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeArchivePath"));
+
+    assert!(!temp_home.path().join(".local/tarsmith/escaped.sh").exists());
+}
+
+#[test]
+fn test_zip_symlink_chain_escape_rejected() {
+    let temp_home = TempDir::new().expect("temp home");
+    let zip_dir = TempDir::new().expect("zip dir");
+    let escaped = temp_home.path().join(".local/tarsmith/ESCAPED_DIR");
+
+    // `x` resolves one level above the extraction root through `s -> .`, although on paper
+    // it stays inside; a directory entry below it must not be created
+    let archive = zip_dir.path().join("evil.zip");
+    let mut writer = zip::ZipWriter::new(File::create(&archive).expect("create zip"));
+    let options = SimpleFileOptions::default();
+    writer.add_symlink("s", ".", options).expect("add link");
+    writer
+        .add_symlink("x", "s/s/..", options)
+        .expect("add link");
+    writer
+        .add_directory("x/ESCAPED_DIR/sub/", options)
+        .expect("add directory");
+    writer.finish().expect("finish zip");
+
+    run_install(temp_home.path(), &archive)
+        .failure()
+        .stderr(predicate::str::contains("UnsafeArchivePath"));
+
+    assert!(!escaped.exists(), "directory created outside the root");
+}
+
+#[test]
+fn test_special_mode_bits_cleared() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_home = TempDir::new().expect("temp home");
+    let archive_dir = TempDir::new().expect("archive dir");
+    let installed = temp_home.path().join(".local/tarsmith");
+    let mode = |path: &Path| fs::metadata(path).expect("stat").permissions().mode() & 0o7777;
+
+    // 1. A setuid executable and a setgid, sticky directory in a tar archive
+    let mut builder = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_size(0);
+    header.set_mode(0o3775);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "suid-1.0/share/", &[][..])
+        .expect("append dir");
+    let data = b"#!/bin/sh\necho root";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o4755);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "suid-1.0/bin/suid", &data[..])
+        .expect("append file");
+    let archive = write_tar(archive_dir.path(), builder);
+
+    // 2. They are installed with plain permissions
+    run_install(temp_home.path(), &archive).success();
+    assert_eq!(mode(&installed.join("suid/1.0/bin/suid")), 0o755);
+    assert_eq!(mode(&installed.join("suid/1.0/share")), 0o775);
+
+    // 3. The same holds for zip archives
+    let archive = archive_dir.path().join("zsuid-1.0.zip");
+    let mut writer = zip::ZipWriter::new(File::create(&archive).expect("create zip"));
+    writer
+        .start_file(
+            "zsuid-1.0/bin/zsuid",
+            SimpleFileOptions::default().unix_permissions(0o6755),
+        )
+        .expect("start file");
+    writer.write_all(data).expect("write file");
+    writer.finish().expect("finish zip");
+
+    run_install(temp_home.path(), &archive).success();
+    assert_eq!(mode(&installed.join("zsuid/1.0/bin/zsuid")), 0o755);
+}
+
+#[test]
+fn test_internal_symlink_allowed() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    let mut builder = tar::Builder::new(Vec::new());
+    append_file(&mut builder, "app/lib/real");
+    append_symlink(&mut builder, "app/bin/app", "../lib/real");
+    let archive = write_tar(tar_dir.path(), builder);
+
+    run_install(temp_home.path(), &archive).success();
+    assert!(
+        temp_home
+            .path()
+            .join(".local/tarsmith/app/current/bin/app")
+            .exists()
+    );
+}
@@ -0,0 +1,157 @@
+// tests/icons.rs
+
+mod common;
+
+use common::{build_archive, file, png, script, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[test]
+fn test_icons_installed_into_hicolor_theme() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let hicolor = home.join(".local/share/icons/hicolor");
+
+    // 1. Icons in several places, plus images that are not the app's icon
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            script("mytool-1.0/bin/mytool", "echo hi"),
+            file("mytool-1.0/resources/mytool.png", png(256, 256), 0o644),
+            file(
+                "mytool-1.0/share/icons/hicolor/48x48/apps/mytool.png",
+                png(48, 48),
+                0o644,
+            ),
+            file(
+                "mytool-1.0/share/icons/hicolor/scalable/apps/mytool.svg",
+                "<svg/>",
+                0o644,
+            ),
+            file("mytool-1.0/icon.png", png(1000, 1000), 0o644),
+            file("mytool-1.0/resources/banner.png", png(128, 128), 0o644),
+            file("mytool-1.0/resources/mytool-wide.png", png(300, 100), 0o644),
+        ],
+    );
+
+    // 2. The dry run lists the copies it would make
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--dry-run", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            hicolor
+                .join("256x256/apps/mytool.png")
+                .display()
+                .to_string(),
+        ))
+        .stdout(predicate::str::contains("Icon=mytool"));
+
+    // 3. Each size goes into its theme directory under the app's name
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed icon:"));
+
+    let installed = [
+        hicolor.join("48x48/apps/mytool.png"),
+        hicolor.join("256x256/apps/mytool.png"),
+        hicolor.join("512x512/apps/mytool.png"),
+        hicolor.join("scalable/apps/mytool.svg"),
+    ];
+    for icon in &installed {
+        assert!(icon.is_file(), "missing {}", icon.display());
+    }
+    assert_eq!(
+        fs::read(hicolor.join("256x256/apps/mytool.png")).unwrap(),
+        png(256, 256)
+    );
+    assert!(!hicolor.join("128x128").exists());
+
+    // 4. The entry refers to the icon by its theme name
+    let entry = fs::read_to_string(home.join(".local/share/applications/mytool.desktop")).unwrap();
+    assert!(entry.lines().any(|l| l == "Icon=mytool"));
+
+    // 5. Uninstall removes the copies
+    tarsmith(home)
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed icon:"));
+    for icon in &installed {
+        assert!(!icon.exists(), "left behind {}", icon.display());
+    }
+}
+
+#[test]
+fn test_no_icon_leaves_icon_key_out() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar",
+            &[script("mytool-1.0/bin/mytool", "echo hi")],
+        ))
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success();
+
+    let entry = fs::read_to_string(home.join(".local/share/applications/mytool.desktop")).unwrap();
+    assert!(!entry.contains("Icon="));
+    assert!(!home.join(".local/share/icons").exists());
+}
+
+#[test]
+fn test_existing_icon_not_replaced() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let hicolor = home.join(".local/share/icons/hicolor");
+    let packaged = hicolor.join("48x48/apps/mytool.png");
+
+    // 1. An icon of the same name that another package installed
+    fs::create_dir_all(packaged.parent().unwrap()).expect("create theme dir");
+    fs::write(&packaged, b"packaged icon").expect("write icon");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            script("mytool-1.0/bin/mytool", "echo hi"),
+            file("mytool-1.0/mytool.png", png(48, 48), 0o644),
+            file("mytool-1.0/resources/mytool.png", png(256, 256), 0o644),
+        ],
+    );
+
+    // 2. The install keeps it and only adds the other size
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept existing icon:"));
+    assert_eq!(fs::read(&packaged).unwrap(), b"packaged icon");
+    assert!(hicolor.join("256x256/apps/mytool.png").is_file());
+
+    // 3. Reinstalling replaces TarSmith's own copy, and uninstall leaves the packaged one
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success();
+    tarsmith(home)
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success();
+    assert_eq!(fs::read(&packaged).unwrap(), b"packaged icon");
+    assert!(!hicolor.join("256x256/apps/mytool.png").exists());
+}
@@ -33,7 +33,7 @@ fn test_successful_installation() {
     let tar_dir = TempDir::new().expect("failed to create tar dir");
     let archive_path = tar_dir.path().join("test_archive.tar");
     let status = std::process::Command::new("tar")
-        .args(&[
+        .args([
             "-cf",
             archive_path.to_str().unwrap(),
             "-C",
@@ -0,0 +1,154 @@
+// tests/launcher_caches.rs
+
+mod common;
+
+use common::{build_archive, file, png, script, tarsmith, write_script};
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[test]
+fn test_mime_cache_updated_on_install_and_uninstall() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let applications = home.join(".local/share/applications");
+    let cache = applications.join("mimeinfo.cache");
+
+    // 1. Launchers already installed, one nested and one hidden
+    fs::create_dir_all(applications.join("kde")).expect("create applications");
+    fs::write(
+        applications.join("other.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Other\nExec=other %f\nMimeType=text/x-mytool;image/png;\n",
+    )
+    .expect("write entry");
+    fs::write(
+        applications.join("kde/viewer.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer %f\nMimeType=image/png;\n",
+    )
+    .expect("write entry");
+    fs::write(
+        applications.join("hidden.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Hidden\nExec=hidden\nMimeType=image/png;\nHidden=true\n",
+    )
+    .expect("write entry");
+
+    // 2. The app ships a launcher that opens its own file type
+    let bundled = "[Desktop Entry]\nType=Application\nName=My Tool\nExec=mytool %f\nMimeType=text/x-mytool;\n";
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            script("mytool-1.0/bin/mytool", "echo hi"),
+            file(
+                "mytool-1.0/share/applications/mytool.desktop",
+                bundled,
+                0o644,
+            ),
+        ],
+    );
+
+    // 3. The dry run lists the cache among its changes
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--dry-run", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(cache.display().to_string()));
+
+    // 4. Installing rebuilds the cache and says the missing tool could not run
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[10] Refreshing launcher caches...",
+        ))
+        .stdout(predicate::str::contains("(2 MIME types)"))
+        .stdout(predicate::str::contains(
+            "update-desktop-database not found",
+        ));
+    assert_eq!(
+        fs::read_to_string(&cache).expect("read cache"),
+        "[MIME Cache]\nimage/png=kde-viewer.desktop;other.desktop;\ntext/x-mytool=mytool.desktop;other.desktop;\n"
+    );
+
+    // 5. Uninstalling drops the app from the cache
+    tarsmith(home)
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mimeinfo.cache (2 MIME types)"));
+    assert_eq!(
+        fs::read_to_string(&cache).expect("read cache"),
+        "[MIME Cache]\nimage/png=kde-viewer.desktop;other.desktop;\ntext/x-mytool=other.desktop;\n"
+    );
+}
+
+#[test]
+fn test_cache_tools_run_when_present() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let tools = home.join("tools");
+    let log = home.join("tools.log");
+    let applications = home.join(".local/share/applications");
+    let hicolor = home.join(".local/share/icons/hicolor");
+
+    // 1. Stand-ins for the desktop-file-utils and GTK tools that log how they are called
+    fs::create_dir_all(&tools).expect("create tools dir");
+    for tool in ["update-desktop-database", "gtk-update-icon-cache"] {
+        write_script(
+            &tools.join(tool),
+            &format!("echo \"${{0##*/}} $*\" >> '{}'", log.display()),
+        );
+    }
+
+    // 2. An app with an icon
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            script("mytool-1.0/bin/mytool", "echo hi"),
+            file("mytool-1.0/mytool.png", png(48, 48), 0o644),
+        ],
+    );
+
+    // 3. Installing runs both tools on the directories written to
+    tarsmith(home)
+        .env("PATH", &tools)
+        .arg(&archive)
+        .args(["--user", "--no-path", "--desktop-exec", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ran update-desktop-database"))
+        .stdout(predicate::str::contains("Ran gtk-update-icon-cache"));
+    let calls = fs::read_to_string(&log).expect("read log");
+    assert!(calls.contains(&format!(
+        "update-desktop-database -q {}",
+        applications.display()
+    )));
+    assert!(calls.contains(&format!(
+        "gtk-update-icon-cache -q -t -f {}",
+        hicolor.display()
+    )));
+
+    // 4. A failing tool is reported without failing the uninstall
+    write_script(
+        &tools.join("gtk-update-icon-cache"),
+        "echo 'cache is locked' >&2\nexit 1",
+    );
+    tarsmith(home)
+        .env("PATH", &tools)
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Warning: gtk-update-icon-cache failed",
+        ))
+        .stdout(predicate::str::contains("cache is locked"))
+        .stdout(predicate::str::contains("Uninstall complete!"));
+    assert!(!applications.join("mytool.desktop").exists());
+}
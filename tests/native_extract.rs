@@ -0,0 +1,93 @@
+// tests/native_extract.rs
+
+mod common;
+
+use common::{script, tar_bytes, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+/// Builds an uncompressed tar stream holding `tool-1.0/bin/tool` with the exec bit set
+fn tool_tar() -> Vec<u8> {
+    tar_bytes(&[script("tool-1.0/bin/tool", "echo tool")])
+}
+
+fn write_archive(dir: &Path, name: &str, bytes: &[u8]) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, bytes).expect("write archive");
+    path
+}
+
+/// Runs a user-level install with no `tar` binary reachable on PATH
+fn install_without_tar(archive: &Path) {
+    let temp_home = TempDir::new().expect("temp home");
+    let empty_path = TempDir::new().expect("empty PATH dir");
+
+    let mut cmd = tarsmith(temp_home.path());
+    cmd.env("PATH", empty_path.path())
+        .arg(archive)
+        .arg("--user")
+        .arg("--no-desktop")
+        .arg("--no-path");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Extraction complete"));
+
+    let tool = temp_home.path().join(".local/tarsmith/tool/1.0/bin/tool");
+    assert!(
+        tool.exists(),
+        "tool not extracted for {}",
+        archive.display()
+    );
+}
+
+#[test]
+fn test_gzip_extraction() {
+    let dir = TempDir::new().expect("tar dir");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&tool_tar()).expect("gzip");
+    let archive = write_archive(dir.path(), "tool-1.0.tar.gz", &encoder.finish().unwrap());
+    install_without_tar(&archive);
+}
+
+#[test]
+fn test_xz_extraction() {
+    let dir = TempDir::new().expect("tar dir");
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&tool_tar()).expect("xz");
+    let archive = write_archive(dir.path(), "tool-1.0.tar.xz", &encoder.finish().unwrap());
+    install_without_tar(&archive);
+}
+
+#[test]
+fn test_bzip2_extraction() {
+    let dir = TempDir::new().expect("tar dir");
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(&tool_tar()).expect("bzip2");
+    let archive = write_archive(dir.path(), "tool-1.0.tar.bz2", &encoder.finish().unwrap());
+    install_without_tar(&archive);
+}
+
+#[test]
+fn test_zstd_extraction() {
+    let dir = TempDir::new().expect("tar dir");
+    let compressed = zstd::encode_all(&tool_tar()[..], 0).expect("zstd");
+    let archive = write_archive(dir.path(), "tool-1.0.tar.zst", &compressed);
+    install_without_tar(&archive);
+}
+
+#[test]
+fn test_corrupt_archive_reports_error() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("tar dir");
+    let archive = write_archive(dir.path(), "broken.tar.gz", b"definitely not gzip");
+
+    let mut cmd = tarsmith(temp_home.path());
+    cmd.arg(&archive).arg("--user");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("ExtractionFailed"));
+}
@@ -0,0 +1,190 @@
+// tests/path_conflicts.rs
+
+mod common;
+
+use assert_cmd::Command;
+use common::{bin_scripts, build_archive};
+use predicates::prelude::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Writes an executable script at `path`
+fn write_command(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Runs tarsmith with ~/.local/bin and `extra_path` as the only PATH entries
+fn tarsmith(home: &Path, extra_path: &Path) -> Command {
+    let mut cmd = common::tarsmith(home);
+    cmd.env(
+        "PATH",
+        format!(
+            "{}:{}",
+            home.join(".local/bin").display(),
+            extra_path.display()
+        ),
+    );
+    cmd
+}
+
+#[test]
+fn test_user_file_is_not_clobbered() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &bin_scripts("mytool-1.0", &["mytool", "mytool-helper"]),
+    );
+
+    // 1. The user's own script sits where the link would go
+    let own_script = home.join(".local/bin/mytool");
+    write_command(&own_script, "#!/bin/sh\necho mine");
+
+    // 2. Non-interactive installs skip the conflicting link and say how to resolve it
+    tarsmith(home, tar_dir.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Conflict: {} is an existing file that is not a symlink",
+            own_script.display()
+        )))
+        .stdout(predicate::str::contains(
+            "Skipped mytool (use --link-prefix",
+        ));
+    assert_eq!(
+        fs::read_to_string(&own_script).unwrap(),
+        "#!/bin/sh\necho mine"
+    );
+    assert!(home.join(".local/bin/mytool-helper").is_symlink());
+
+    // 3. A prefix installs it under another name, and uninstall leaves the user's script alone
+    tarsmith(home, tar_dir.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--link-prefix", "tarsmith-"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created symlink: tarsmith-mytool"));
+    assert!(home.join(".local/bin/tarsmith-mytool").is_symlink());
+    assert!(home.join(".local/bin/mytool-helper").is_symlink());
+
+    tarsmith(home, tar_dir.path())
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success();
+    assert!(!home.join(".local/bin/tarsmith-mytool").exists());
+    assert_eq!(
+        fs::read_to_string(&own_script).unwrap(),
+        "#!/bin/sh\necho mine"
+    );
+
+    // 4. --force replaces it
+    tarsmith(home, tar_dir.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Creating it anyway (--force)"));
+    assert!(own_script.is_symlink());
+}
+
+#[test]
+fn test_other_install_and_shadowed_commands() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let system_bin = TempDir::new().expect("system bin");
+    let home = temp_home.path();
+
+    // 1. Another app already provides `shared`, and `python3` exists elsewhere on PATH
+    write_command(&system_bin.path().join("python3"), "#!/bin/sh\necho system");
+    tarsmith(home, system_bin.path())
+        .arg(build_archive(
+            tar_dir.path(),
+            "alpha-1.0.tar",
+            &bin_scripts("alpha-1.0", &["alpha", "shared"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+
+    // 2. Both conflicts are reported and skipped
+    tarsmith(home, system_bin.path())
+        .arg(build_archive(
+            tar_dir.path(),
+            "beta-1.0.tar",
+            &bin_scripts("beta-1.0", &["beta", "python3", "shared"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is a symlink installed for alpha"))
+        .stdout(predicate::str::contains(format!(
+            "is the same command at {}",
+            system_bin.path().join("python3").display()
+        )));
+
+    assert!(home.join(".local/bin/beta").is_symlink());
+    assert!(!home.join(".local/bin/python3").exists());
+    let shared = fs::read_link(home.join(".local/bin/shared")).unwrap();
+    assert!(shared.starts_with(home.join(".local/tarsmith/alpha")));
+}
+
+#[test]
+fn test_interactive_rename() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    write_command(&home.join(".local/bin/mytool"), "#!/bin/sh\necho mine");
+
+    // User-level, no desktop entry, add to PATH, rename the conflicting link
+    tarsmith(home, tar_dir.path())
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar",
+            &bin_scripts("mytool-1.0", &["mytool"]),
+        ))
+        .write_stdin("1\n\n\nr\nmt\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skip, rename or create it anyway?",
+        ))
+        .stdout(predicate::str::contains("Created symlink: mt ->"));
+
+    assert!(home.join(".local/bin/mt").is_symlink());
+    let record = fs::read_to_string(home.join(".local/share/tarsmith/mytool.json")).unwrap();
+    assert!(record.contains(".local/bin/mt\""));
+}
+
+#[test]
+fn test_dry_run_reports_conflicts() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    write_command(&home.join(".local/bin/mytool"), "#!/bin/sh\necho mine");
+
+    tarsmith(home, tar_dir.path())
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar",
+            &bin_scripts("mytool-1.0", &["mytool"]),
+        ))
+        .args(["--dry-run", "--link-suffix", "-1.0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "is an existing file that is not a symlink; renamed to mytool-1.0",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "create   symlink  {}",
+            home.join(".local/bin/mytool-1.0").display()
+        )));
+}
@@ -27,7 +27,7 @@ fn test_path_symlink_created() {
     let tar_dir = TempDir::new().expect("tar dir");
     let archive_path = tar_dir.path().join("myapp.tar");
     let status = std::process::Command::new("tar")
-        .args(&[
+        .args([
             "-cf",
             archive_path.to_str().unwrap(),
             "-C",
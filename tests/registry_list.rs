@@ -0,0 +1,84 @@
+// tests/registry_list.rs
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[test]
+fn test_install_is_recorded_and_listed() {
+    // 1. Setup isolated environment
+    let temp_home = TempDir::new().expect("temp home");
+    let source_dir = TempDir::new().expect("source dir");
+
+    // 2. Create dummy executable
+    let exe_path = source_dir.path().join("myapp");
+    fs::write(&exe_path, "#!/bin/sh\necho ok").expect("write exe");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&exe_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&exe_path, perms).expect("set exec perms");
+    }
+
+    // 3. Create tar archive (outside source dir)
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive_path = tar_dir.path().join("myapp.tar");
+    let status = std::process::Command::new("tar")
+        .args([
+            "-cf",
+            archive_path.to_str().unwrap(),
+            "-C",
+            source_dir.path().to_str().unwrap(),
+            ".",
+        ])
+        .status()
+        .expect("tar");
+    assert!(status.success(), "tar command failed");
+
+    // 4. Run installer
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", temp_home.path())
+        .arg(&archive_path)
+        .args(["--user", "--desktop-exec", "myapp"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Install recorded at"));
+
+    // 5. Manifest exists and records the side effects
+    let manifest = temp_home.path().join(".local/share/tarsmith/myapp.json");
+    let contents = fs::read_to_string(&manifest).expect("manifest written");
+    assert!(
+        contents.contains("myapp.tar"),
+        "source archive not recorded"
+    );
+    assert!(
+        contents.contains(".local/bin/myapp"),
+        "symlink not recorded"
+    );
+    assert!(
+        contents.contains("myapp.desktop"),
+        "desktop file not recorded"
+    );
+
+    // 6. `list` prints the recorded app
+    let mut list = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    list.env("HOME", temp_home.path()).arg("list");
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains("myapp (user)"))
+        .stdout(predicate::str::contains("myapp.tar"));
+}
+
+#[test]
+fn test_list_without_installs() {
+    let temp_home = TempDir::new().expect("temp home");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", temp_home.path()).arg("list");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No apps installed"));
+}
@@ -0,0 +1,155 @@
+// tests/rollback.rs
+
+mod common;
+
+use assert_cmd::Command;
+use common::{build_archive, file, script, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+fn installer(home: &Path, archive: &Path) -> Command {
+    let mut cmd = tarsmith(home);
+    cmd.arg(archive).args(["--user", "--desktop-exec", "myapp"]);
+    cmd
+}
+
+#[test]
+fn test_failed_reinstall_keeps_previous_version() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Install a working version
+    installer(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &[
+                script("myapp/bin/myapp", "echo 1.0"),
+                file("myapp/VERSION", "1.0", 0o644),
+            ],
+        ),
+    )
+    .assert()
+    .success();
+    let desktop = home.join(".local/share/applications/myapp.desktop");
+    let desktop_before = fs::read_to_string(&desktop).expect("desktop entry");
+
+    // 2. Reinstall a broken version with no executables
+    installer(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "myapp-2.0.tar",
+            &[
+                file("myapp/bin/myapp", "#!/bin/sh\necho 2.0\n", 0o644),
+                file("myapp/VERSION", "2.0", 0o644),
+            ],
+        ),
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("Rolling back"))
+    .stderr(predicate::str::contains("NoExecutableFound"));
+
+    // 3. The previous tree and its integration are untouched
+    let install_dir = home.join(".local/tarsmith");
+    let version =
+        fs::read_to_string(install_dir.join("myapp/current/VERSION")).expect("old tree kept");
+    assert_eq!(version, "1.0");
+    assert!(!install_dir.join("myapp/2.0").exists());
+    assert_eq!(fs::read_to_string(&desktop).unwrap(), desktop_before);
+    assert!(home.join(".local/bin/myapp").exists());
+
+    let leftovers: Vec<_> = fs::read_dir(&install_dir)
+        .unwrap()
+        .chain(fs::read_dir(install_dir.join("myapp")).unwrap())
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with('.'))
+        .collect();
+    assert!(leftovers.is_empty(), "staging left behind: {:?}", leftovers);
+}
+
+#[test]
+fn test_late_failure_rolls_back_every_step() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // Block the install record so the very last step fails
+    fs::create_dir_all(home.join(".local/share")).unwrap();
+    fs::write(home.join(".local/share/tarsmith"), "not a directory").unwrap();
+    fs::write(home.join(".bashrc"), "# user config\n").unwrap();
+
+    installer(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &[
+                script("myapp/bin/myapp", "echo 1.0"),
+                file("myapp/VERSION", "1.0", 0o644),
+            ],
+        ),
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("Rolling back"));
+
+    assert!(!home.join(".local/tarsmith/myapp").exists());
+    assert!(!home.join(".local/bin/myapp").is_symlink());
+    assert!(
+        !home
+            .join(".local/share/applications/myapp.desktop")
+            .exists()
+    );
+    assert_eq!(
+        fs::read_to_string(home.join(".bashrc")).unwrap(),
+        "# user config\n"
+    );
+}
+
+#[test]
+fn test_reinstall_replaces_and_cleans_backups() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    installer(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &[
+                script("myapp/bin/myapp", "echo 1.0"),
+                file("myapp/VERSION", "1.0", 0o644),
+            ],
+        ),
+    )
+    .assert()
+    .success();
+    installer(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "myapp-2.0.tar",
+            &[
+                script("myapp/bin/myapp", "echo 2.0"),
+                file("myapp/VERSION", "2.0", 0o644),
+            ],
+        ),
+    )
+    .assert()
+    .success();
+
+    let app_dir = home.join(".local/tarsmith/myapp");
+    let version = fs::read_to_string(app_dir.join("current/VERSION")).unwrap();
+    assert_eq!(version, "2.0");
+    assert!(app_dir.join("1.0/VERSION").exists());
+    assert!(!app_dir.join(".current.tarsmith-backup").exists());
+    assert!(!home.join(".local/bin/.myapp.tarsmith-backup").exists());
+}
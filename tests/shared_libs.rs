@@ -0,0 +1,170 @@
+// tests/shared_libs.rs
+
+mod common;
+
+use common::{build_archive, elf, elf_with_needed, exe, file, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[test]
+fn test_missing_library_reported() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            exe(
+                "mytool-1.0/bin/mytool",
+                elf_with_needed(&["libtarsmith-missing.so.1", "libbundled.so"]),
+            ),
+            file("mytool-1.0/lib/libbundled.so", elf(64), 0o644),
+        ],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "mytool: missing libtarsmith-missing.so.1\n",
+        ))
+        .stdout(predicate::str::contains("1 shared library not found"));
+}
+
+#[test]
+fn test_strict_deps_aborts_install() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            exe(
+                "mytool-1.0/bin/mytool",
+                elf_with_needed(&["libtarsmith-missing.so.1"]),
+            ),
+            file("mytool-1.0/lib/libbundled.so", elf(64), 0o644),
+        ],
+    );
+
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--strict-deps"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MissingLibraries"))
+        .stderr(predicate::str::contains("Rolling back"));
+
+    let home = temp_home.path();
+    assert!(!home.join(".local/tarsmith/mytool").exists());
+    assert!(!home.join(".local/bin/mytool").is_symlink());
+}
+
+#[test]
+fn test_bundled_and_ld_library_path_resolved() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let lib_dir = TempDir::new().expect("lib dir");
+    fs::write(lib_dir.path().join("libextra.so.2"), elf(64)).unwrap();
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            exe(
+                "mytool-1.0/bin/mytool",
+                elf_with_needed(&["libbundled.so", "libextra.so.2"]),
+            ),
+            file("mytool-1.0/lib/libbundled.so", elf(64), 0o644),
+        ],
+    );
+
+    tarsmith(temp_home.path())
+        .env("LD_LIBRARY_PATH", lib_dir.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--strict-deps"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All shared libraries found"));
+}
+
+#[test]
+fn test_library_of_other_class_not_resolved() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let lib_dir = TempDir::new().expect("lib dir");
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            exe("mytool-1.0/bin/mytool", elf_with_needed(&["libextra.so.2"])),
+            file("mytool-1.0/lib/libbundled.so", elf(64), 0o644),
+        ],
+    );
+
+    // 1. Only a 32-bit build of the library is on the search path
+    let mut lib32 = elf(64);
+    lib32[4] = 1; // ELFCLASS32
+    fs::write(lib_dir.path().join("libextra.so.2"), lib32).unwrap();
+    tarsmith(temp_home.path())
+        .env("LD_LIBRARY_PATH", lib_dir.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--strict-deps"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MissingLibraries"));
+
+    // 2. A matching build further down the path is found
+    let lib64_dir = TempDir::new().expect("lib dir");
+    fs::write(lib64_dir.path().join("libextra.so.2"), elf(64)).unwrap();
+    tarsmith(temp_home.path())
+        .env(
+            "LD_LIBRARY_PATH",
+            format!(
+                "{}:{}",
+                lib_dir.path().display(),
+                lib64_dir.path().display()
+            ),
+        )
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--strict-deps"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All shared libraries found"));
+}
+
+#[test]
+fn test_overflowing_segment_bounds_ignored() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+
+    // 1. PT_LOAD ends past u64::MAX, and DT_STRTAB points inside it
+    let mut bytes = elf_with_needed(&["libtarsmith-missing.so.1"]);
+    bytes[80..88].copy_from_slice(&(u64::MAX - 8).to_le_bytes()); // p_vaddr
+    bytes[96..104].copy_from_slice(&1024u64.to_le_bytes()); // p_filesz
+    let strtab = [5u64.to_le_bytes(), 176u64.to_le_bytes()].concat();
+    let at = bytes
+        .windows(strtab.len())
+        .position(|w| w == strtab.as_slice())
+        .expect("DT_STRTAB entry");
+    bytes[at + 8..at + 16].copy_from_slice(&(u64::MAX - 4).to_le_bytes());
+    let archive = build_archive(
+        tar_dir.path(),
+        "mytool-1.0.tar",
+        &[
+            exe("mytool-1.0/bin/mytool", bytes),
+            file("mytool-1.0/lib/libbundled.so", elf(64), 0o644),
+        ],
+    );
+
+    // 2. The libraries cannot be read, but the install goes on
+    tarsmith(temp_home.path())
+        .arg(&archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("panicked").not());
+}
@@ -0,0 +1,214 @@
+// tests/signature.rs
+
+mod common;
+
+use assert_cmd::Command;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blake2::{Blake2b512, Digest};
+use common::{build_archive, script, tarsmith};
+use ed25519_dalek::{Signer, SigningKey};
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Writes a minisign/signify-style public key file for `key`
+fn write_public_key(dir: &Path, key: &SigningKey) -> PathBuf {
+    let mut payload = b"Ed".to_vec();
+    payload.extend_from_slice(&KEY_ID);
+    payload.extend_from_slice(key.verifying_key().as_bytes());
+    let path = dir.join("key.pub");
+    fs::write(
+        &path,
+        format!(
+            "untrusted comment: test key\n{}\n",
+            STANDARD.encode(payload)
+        ),
+    )
+    .expect("write public key");
+    path
+}
+
+/// Signs `archive` the way `minisign -S` does (prehashed BLAKE2b-512)
+fn write_minisign_signature(archive: &Path, key: &SigningKey) -> PathBuf {
+    let digest = Blake2b512::digest(fs::read(archive).unwrap());
+    let signature = key.sign(&digest).to_bytes();
+    let trusted_comment = "timestamp:0\tfile:tool-1.0.tar";
+    let mut global = signature.to_vec();
+    global.extend_from_slice(trusted_comment.as_bytes());
+    let global_signature = key.sign(&global).to_bytes();
+
+    let mut payload = b"ED".to_vec();
+    payload.extend_from_slice(&KEY_ID);
+    payload.extend_from_slice(&signature);
+
+    let path = archive.with_extension("tar.minisig");
+    fs::write(
+        &path,
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            STANDARD.encode(payload),
+            trusted_comment,
+            STANDARD.encode(global_signature)
+        ),
+    )
+    .expect("write minisig");
+    path
+}
+
+/// Signs `archive` the way `signify -S` does (plain Ed25519 over the file)
+fn write_signify_signature(archive: &Path, key: &SigningKey) -> PathBuf {
+    let signature = key.sign(&fs::read(archive).unwrap()).to_bytes();
+    let mut payload = b"Ed".to_vec();
+    payload.extend_from_slice(&KEY_ID);
+    payload.extend_from_slice(&signature);
+
+    let path = archive.with_extension("tar.sig");
+    fs::write(
+        &path,
+        format!(
+            "untrusted comment: verify with key.pub\n{}\n",
+            STANDARD.encode(payload)
+        ),
+    )
+    .expect("write signify sig");
+    path
+}
+
+fn installer(home: &Path, archive: &Path) -> Command {
+    let mut cmd = tarsmith(home);
+    cmd.arg(archive)
+        .arg("--user")
+        .arg("--no-desktop")
+        .arg("--no-path");
+    cmd
+}
+
+#[test]
+fn test_minisign_signature_verifies() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("work dir");
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let archive = build_archive(
+        dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+    let pubkey = write_public_key(dir.path(), &key);
+    let signature = write_minisign_signature(&archive, &key);
+
+    installer(temp_home.path(), &archive)
+        .arg("--signature")
+        .arg(&signature)
+        .arg("--pubkey")
+        .arg(&pubkey)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("minisign signature verified"));
+}
+
+#[test]
+fn test_signify_signature_with_pinned_key() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("work dir");
+    let key = SigningKey::from_bytes(&[9u8; 32]);
+    let archive = build_archive(
+        dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+    let signature = write_signify_signature(&archive, &key);
+
+    // Pin the key instead of passing --pubkey
+    let keys_dir = temp_home.path().join(".config/tarsmith/keys");
+    fs::create_dir_all(&keys_dir).expect("keys dir");
+    let pubkey = write_public_key(dir.path(), &key);
+    fs::copy(&pubkey, keys_dir.join("tool.pub")).expect("pin key");
+
+    installer(temp_home.path(), &archive)
+        .arg("--signature")
+        .arg(&signature)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("signify signature verified"));
+}
+
+#[test]
+fn test_tampered_archive_is_refused() {
+    let temp_home = TempDir::new().expect("temp home");
+    let dir = TempDir::new().expect("work dir");
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    let archive = build_archive(
+        dir.path(),
+        "tool-1.0.tar",
+        &[script("tool-1.0/bin/tool", "echo tool")],
+    );
+    let pubkey = write_public_key(dir.path(), &key);
+    let signature = write_minisign_signature(&archive, &key);
+
+    let mut bytes = fs::read(&archive).unwrap();
+    bytes[600] ^= 0xff;
+    fs::write(&archive, bytes).unwrap();
+
+    installer(temp_home.path(), &archive)
+        .arg("--signature")
+        .arg(&signature)
+        .arg("--pubkey")
+        .arg(&pubkey)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Extracting archive").not())
+        .stderr(predicate::str::contains("SignatureVerificationFailed"));
+}
+
+#[test]
+fn test_openpgp_armored_and_binary_signatures() {
+    for signature in ["signed-tool-1.0.tar.asc", "signed-tool-1.0.tar.sig"] {
+        let temp_home = TempDir::new().expect("temp home");
+
+        installer(temp_home.path(), &fixture("signed-tool-1.0.tar"))
+            .arg("--signature")
+            .arg(fixture(signature))
+            .arg("--pubkey")
+            .arg(fixture("openpgp-pubkey.asc"))
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("OpenPGP signature verified"));
+    }
+}
+
+#[test]
+fn test_openpgp_wrong_key_is_refused() {
+    let temp_home = TempDir::new().expect("temp home");
+
+    installer(temp_home.path(), &fixture("signed-tool-1.0.tar"))
+        .arg("--signature")
+        .arg(fixture("signed-tool-1.0.tar.asc"))
+        .arg("--pubkey")
+        .arg(fixture("openpgp-other-pubkey.asc"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("SignatureVerificationFailed"));
+}
+
+#[test]
+fn test_signature_without_any_key() {
+    let temp_home = TempDir::new().expect("temp home");
+
+    installer(temp_home.path(), &fixture("signed-tool-1.0.tar"))
+        .arg("--signature")
+        .arg(fixture("signed-tool-1.0.tar.asc"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no --pubkey given"));
+}
@@ -0,0 +1,176 @@
+// tests/switch.rs
+
+mod common;
+
+use common::{build_archive, script, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+fn install(home: &Path, archive: &Path) {
+    tarsmith(home)
+        .arg(archive)
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_versions_installed_side_by_side() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Install two versions
+    install(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "node-v22.1.0-linux-x64.tar",
+            &[script("node-v22.1.0-linux-x64/bin/node", "echo 22.1.0")],
+        ),
+    );
+    install(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "node-v24.11.1-linux-x64.tar",
+            &[script("node-v24.11.1-linux-x64/bin/node", "echo 24.11.1")],
+        ),
+    );
+
+    // 2. Both trees exist and `current` points at the newest install
+    let app_dir = home.join(".local/tarsmith/node");
+    assert!(app_dir.join("22.1.0/bin/node").exists());
+    assert!(app_dir.join("24.11.1/bin/node").exists());
+    assert_eq!(
+        fs::read_link(app_dir.join("current")).unwrap(),
+        PathBuf::from("24.11.1")
+    );
+
+    // 3. The PATH symlink resolves through `current`
+    let link = fs::read_link(home.join(".local/bin/node")).expect("PATH symlink");
+    assert_eq!(link, app_dir.join("current/bin/node"));
+
+    tarsmith(home)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Version: 24.11.1 (installed: 22.1.0, 24.11.1)",
+        ));
+}
+
+#[test]
+fn test_switch_flips_current_without_reextracting() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    let old = build_archive(
+        tar_dir.path(),
+        "node-v22.1.0-linux-x64.tar",
+        &[script("node-v22.1.0-linux-x64/bin/node", "echo 22.1.0")],
+    );
+    install(home, &old);
+    install(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "node-v24.11.1-linux-x64.tar",
+            &[script("node-v24.11.1-linux-x64/bin/node", "echo 24.11.1")],
+        ),
+    );
+
+    // 1. Remove the archive so switching cannot re-extract it
+    fs::remove_file(&old).unwrap();
+
+    // 2. Switch back to the older version
+    tarsmith(home)
+        .args(["switch", "node", "22.1.0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Switched node to version 22.1.0"));
+
+    let app_dir = home.join(".local/tarsmith/node");
+    assert_eq!(
+        fs::read_link(app_dir.join("current")).unwrap(),
+        PathBuf::from("22.1.0")
+    );
+    let script = fs::read_to_string(home.join(".local/bin/node")).expect("PATH symlink");
+    assert!(script.contains("echo 22.1.0"));
+
+    // 3. The record follows the switch
+    let record = fs::read_to_string(home.join(".local/share/tarsmith/node.json")).unwrap();
+    assert!(record.contains("\"version\": \"22.1.0\""));
+}
+
+#[test]
+fn test_switch_to_missing_version() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    install(
+        home,
+        &build_archive(
+            tar_dir.path(),
+            "node-v24.11.1-linux-x64.tar",
+            &[script("node-v24.11.1-linux-x64/bin/node", "echo 24.11.1")],
+        ),
+    );
+
+    tarsmith(home)
+        .args(["switch", "node", "1.0.0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("VersionNotInstalled"));
+
+    assert_eq!(
+        fs::read_link(home.join(".local/tarsmith/node/current")).unwrap(),
+        PathBuf::from("24.11.1")
+    );
+}
+
+#[test]
+fn test_foreign_app_directory_kept() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let app_dir = home.join(".local/tarsmith/node");
+    let archive = build_archive(
+        tar_dir.path(),
+        "node-v24.11.1-linux-x64.tar",
+        &[script("node-v24.11.1-linux-x64/bin/node", "echo 24.11.1")],
+    );
+
+    // 1. A node tree unpacked by hand where TarSmith would put its own
+    fs::create_dir_all(app_dir.join("bin")).expect("create foreign dir");
+    fs::write(app_dir.join("bin/node"), "#!/bin/sh\necho mine").expect("write foreign file");
+
+    // 2. Neither the install nor its dry run touch it
+    for extra in [&["--dry-run"][..], &[]] {
+        tarsmith(home)
+            .arg(&archive)
+            .args(["--user", "--no-desktop"])
+            .args(extra)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("ForeignAppDirectory"));
+    }
+    assert_eq!(
+        fs::read_to_string(app_dir.join("bin/node")).expect("foreign file kept"),
+        "#!/bin/sh\necho mine"
+    );
+
+    // 3. --force replaces it
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--no-desktop", "--force"])
+        .assert()
+        .success();
+    assert!(!app_dir.join("bin").exists());
+    assert!(app_dir.join("current/bin/node").exists());
+}
@@ -0,0 +1,156 @@
+// tests/uninstall.rs
+
+mod common;
+
+use assert_cmd::Command;
+use common::{bin_scripts, build_archive, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// Builds `myapp.tar` containing a single executable and installs it under `home`
+fn install_myapp(home: &Path, tar_dir: &Path) {
+    let source_dir = TempDir::new().expect("source dir");
+    let exe_path = source_dir.path().join("myapp");
+    fs::write(&exe_path, "#!/bin/sh\necho ok").expect("write exe");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&exe_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&exe_path, perms).expect("set exec perms");
+    }
+
+    let archive_path = tar_dir.join("myapp.tar");
+    let status = std::process::Command::new("tar")
+        .args([
+            "-cf",
+            archive_path.to_str().unwrap(),
+            "-C",
+            source_dir.path().to_str().unwrap(),
+            ".",
+        ])
+        .status()
+        .expect("tar");
+    assert!(status.success(), "tar command failed");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", home)
+        .env("PATH", "/usr/bin:/bin")
+        .env("SHELL", "/bin/bash")
+        .arg(&archive_path)
+        .args(["--user", "--desktop-exec", "myapp"]);
+    cmd.assert().success();
+}
+
+#[test]
+fn test_uninstall_removes_side_effects() {
+    // 1. Install
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    install_myapp(temp_home.path(), tar_dir.path());
+
+    let home = temp_home.path();
+    assert!(home.join(".local/bin/myapp").exists());
+    assert!(
+        home.join(".local/share/applications/myapp.desktop")
+            .exists()
+    );
+    let bashrc = fs::read_to_string(home.join(".bashrc")).expect("bashrc written");
+    assert!(bashrc.contains("# Added by TarSmith installer"));
+
+    // 2. Uninstall
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", home).args(["uninstall", "myapp"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Uninstall complete"));
+
+    // 3. Everything the install created is gone
+    assert!(!home.join(".local/bin/myapp").is_symlink());
+    assert!(
+        !home
+            .join(".local/share/applications/myapp.desktop")
+            .exists()
+    );
+    assert!(!home.join(".local/tarsmith/myapp").exists());
+    assert!(!home.join(".local/share/tarsmith/myapp.json").exists());
+    let bashrc = fs::read_to_string(home.join(".bashrc")).expect("bashrc kept");
+    assert!(!bashrc.contains("# Added by TarSmith installer"));
+}
+
+#[test]
+fn test_uninstall_keeps_foreign_symlink_target() {
+    // 1. Install, then replace the symlink with the user's own script
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    install_myapp(temp_home.path(), tar_dir.path());
+
+    let link = temp_home.path().join(".local/bin/myapp");
+    fs::remove_file(&link).expect("remove symlink");
+    fs::write(&link, "#!/bin/sh\necho mine").expect("write own script");
+
+    // 2. Uninstall leaves the user's file alone
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", temp_home.path())
+        .args(["uninstall", "myapp"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("no longer a symlink"));
+
+    let contents = fs::read_to_string(&link).expect("own script kept");
+    assert!(contents.contains("mine"));
+}
+
+#[test]
+fn test_uninstall_unknown_app() {
+    let temp_home = TempDir::new().expect("temp home");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", temp_home.path())
+        .args(["uninstall", "nope"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("AppNotInstalled"));
+}
+
+#[test]
+fn test_uninstall_keeps_path_export_it_did_not_add() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let bashrc = home.join(".bashrc");
+    let install = |name: &str| {
+        tarsmith(home)
+            .env("PATH", "/usr/bin:/bin")
+            .arg(build_archive(
+                tar_dir.path(),
+                &format!("{}-1.0.tar", name),
+                &bin_scripts(&format!("{}-1.0", name), &[name]),
+            ))
+            .args(["--user", "--no-desktop"])
+            .assert()
+            .success();
+    };
+    let uninstall = |name: &str| {
+        tarsmith(home).args(["uninstall", name]).assert().success();
+    };
+
+    // 1. An export added before this install, e.g. by an older TarSmith
+    let export = "# Added by TarSmith installer\nexport PATH=\"$HOME/.local/bin:$PATH\"\n";
+    fs::write(&bashrc, export).expect("write bashrc");
+    install("alpha");
+    let record = fs::read_to_string(home.join(".local/share/tarsmith/alpha.json")).unwrap();
+    assert!(!record.contains(".bashrc"));
+    uninstall("alpha");
+    assert_eq!(fs::read_to_string(&bashrc).unwrap(), export);
+
+    // 2. An export this install added stays while a later app still has PATH links
+    fs::remove_file(&bashrc).expect("remove bashrc");
+    install("alpha");
+    install("beta");
+    uninstall("alpha");
+    assert_eq!(fs::read_to_string(&bashrc).unwrap(), export);
+}
@@ -0,0 +1,303 @@
+// tests/upgrade.rs
+
+mod common;
+
+use common::{bin_scripts, build_archive, tarsmith};
+use predicates::prelude::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+fn desktop_exec(home: &Path) -> String {
+    let contents = fs::read_to_string(home.join(".local/share/applications/myapp.desktop"))
+        .expect("desktop entry");
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("Exec="))
+        .expect("Exec line")
+        .to_string()
+}
+
+#[test]
+fn test_upgrade_keeps_choices_and_reports_new_executables() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. Install 1.0 with the desktop entry and every executable on PATH
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &bin_scripts("myapp-1.0", &["alpha", "beta"]),
+        ))
+        .args(["--user", "--desktop-exec", "alpha"])
+        .assert()
+        .success();
+    let chosen = Path::new(&desktop_exec(home))
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    // 2. Upgrade to 2.0, which ships an extra executable, without any prompt input
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-2.0.tar",
+            &bin_scripts("myapp-2.0", &["alpha", "beta", "gamma"]),
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Upgrading myapp from 1.0 to 2.0"))
+        .stdout(predicate::str::contains(
+            "New executables (not added to PATH): gamma",
+        ));
+
+    // 3. The same desktop executable and PATH selection are used for the new version
+    let app_dir = home.join(".local/tarsmith/myapp");
+    assert_eq!(
+        fs::read_link(app_dir.join("current")).unwrap(),
+        PathBuf::from("2.0")
+    );
+    assert_eq!(
+        desktop_exec(home),
+        app_dir
+            .join("current/bin")
+            .join(&chosen)
+            .display()
+            .to_string()
+    );
+    assert!(home.join(".local/bin/alpha").is_symlink());
+    assert!(home.join(".local/bin/beta").is_symlink());
+    assert!(!home.join(".local/bin/gamma").exists());
+}
+
+#[test]
+fn test_upgrade_reports_removed_executables() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &bin_scripts("myapp-1.0", &["alpha", "beta"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.1.tar",
+            &bin_scripts("myapp-1.1", &["alpha"]),
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Executables no longer shipped: beta",
+        ));
+
+    assert!(home.join(".local/bin/alpha").is_symlink());
+    assert!(!home.join(".local/bin/beta").is_symlink());
+}
+
+#[test]
+fn test_upgrade_refuses_downgrade() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.10.tar",
+            &bin_scripts("myapp-1.10", &["alpha"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+    let older = build_archive(
+        tar_dir.path(),
+        "myapp-1.9.tar",
+        &bin_scripts("myapp-1.9", &["alpha"]),
+    );
+
+    // 1. 1.9 is older than 1.10
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(&older)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NotNewerVersion"));
+    assert!(!home.join(".local/tarsmith/myapp/1.9").exists());
+
+    // 2. Allowed explicitly
+    tarsmith(home)
+        .args(["upgrade", "myapp", "--allow-downgrade"])
+        .arg(&older)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_link(home.join(".local/tarsmith/myapp/current")).unwrap(),
+        PathBuf::from("1.9")
+    );
+}
+
+#[test]
+fn test_upgrade_rejects_other_app() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &bin_scripts("myapp-1.0", &["alpha"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(build_archive(
+            tar_dir.path(),
+            "otherapp-2.0.tar",
+            &bin_scripts("otherapp-2.0", &["alpha"]),
+        ))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ArchiveAppMismatch"));
+}
+
+#[test]
+fn test_upgrade_ignores_case_and_separators_in_name() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    // 1. The vendor spells the name differently from one release to the next
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "ideaIU-1.0.tar",
+            &bin_scripts("ideaIU-1.0", &["idea"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+
+    // 2. The upgrade still recognizes the app
+    tarsmith(home)
+        .args(["upgrade", "ideaIU"])
+        .arg(build_archive(
+            tar_dir.path(),
+            "idea-IU-2.0.tar",
+            &bin_scripts("idea-IU-2.0", &["idea"]),
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installation complete!"));
+    assert_eq!(
+        fs::read_link(home.join(".local/tarsmith/ideaIU/current")).expect("current link"),
+        Path::new("2.0")
+    );
+}
+
+#[test]
+fn test_upgrade_verifies_checksum() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &bin_scripts("myapp-1.0", &["alpha"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+    let newer = build_archive(
+        tar_dir.path(),
+        "myapp-2.0.tar",
+        &bin_scripts("myapp-2.0", &["alpha"]),
+    );
+    let digest: String = Sha256::digest(fs::read(&newer).expect("read archive"))
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    // 1. A wrong digest stops the upgrade before anything is installed
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(&newer)
+        .args(["--sha256", &"0".repeat(64)])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ChecksumMismatch"));
+    assert!(!home.join(".local/tarsmith/myapp/2.0").exists());
+
+    // 2. The right one is checked and recorded
+    tarsmith(home)
+        .args(["upgrade", "myapp"])
+        .arg(&newer)
+        .args(["--sha256", &digest])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SHA-256 verified"));
+    let record = fs::read_to_string(home.join(".local/share/tarsmith/myapp.json")).unwrap();
+    assert!(record.contains("\"sha256_verified\": true"));
+}
+
+#[test]
+fn test_plain_install_retires_previous_links() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let bin = home.join(".local/bin");
+
+    // 1. 1.0 ships two executables, both linked
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-1.0.tar",
+            &bin_scripts("myapp-1.0", &["alpha", "beta"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success();
+    assert!(bin.join("beta").is_symlink());
+
+    // 2. Installing 2.0 without `upgrade` still removes the link it no longer creates
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "myapp-2.0.tar",
+            &bin_scripts("myapp-2.0", &["alpha"]),
+        ))
+        .args(["--user", "--no-desktop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed symlink:"));
+    assert!(!bin.join("beta").is_symlink());
+    assert!(bin.join("alpha").is_symlink());
+
+    // 3. Uninstall leaves nothing behind
+    tarsmith(home)
+        .args(["uninstall", "myapp"])
+        .assert()
+        .success();
+    assert!(!bin.join("alpha").is_symlink());
+    assert!(!bin.join("beta").is_symlink());
+}
@@ -0,0 +1,146 @@
+// tests/wrapper_launcher.rs
+
+mod common;
+
+use common::{Entry, build_archive, script, tarsmith};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use tempfile::TempDir;
+
+/// The `bin/mytool` script of `mytool-<version>`, reporting how it was launched
+fn launcher(version: &str) -> Entry {
+    script(
+        format!("mytool-{}/bin/mytool", version),
+        &format!(
+            "echo \"version={} argv0=$0 home=$MYTOOL_HOME pwd=$(pwd) args=$*\"",
+            version
+        ),
+    )
+}
+
+/// Runs the installed launcher and returns its output
+fn run_launcher(home: &Path) -> String {
+    let output = std::process::Command::new(home.join(".local/bin/mytool"))
+        .args(["a", "b"])
+        .output()
+        .expect("run launcher");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_wrapper_sets_env_and_working_directory() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let current = home.join(".local/tarsmith/mytool/current");
+
+    // 1. Install with a wrapper that sets a variable and changes into the app directory
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar",
+            &[launcher("1.0")],
+        ))
+        .args([
+            "--user",
+            "--no-desktop",
+            "--link-mode",
+            "wrapper",
+            "--wrapper-env",
+            "MYTOOL_HOME=$APP_DIR/share",
+            "--wrapper-cwd",
+            "$APP_DIR",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created wrapper: mytool ->"));
+
+    // 2. The launcher is a marked script, not a symlink
+    let launcher = home.join(".local/bin/mytool");
+    assert!(!launcher.is_symlink());
+    let contents = fs::read_to_string(&launcher).unwrap();
+    assert!(contents.contains("# Launcher generated by TarSmith for mytool"));
+
+    // 3. The real binary runs under its own path with the configured environment
+    let output = run_launcher(home);
+    assert!(output.contains(&format!("argv0={}", current.join("bin/mytool").display())));
+    assert!(output.contains(&format!("home={}", current.join("share").display())));
+    assert!(output.contains(&format!("pwd={}", current.display())));
+    assert!(output.contains("args=a b"));
+}
+
+#[test]
+fn test_upgrade_keeps_wrappers_and_uninstall_removes_them() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+
+    tarsmith(home)
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-1.0.tar",
+            &[launcher("1.0")],
+        ))
+        .args(["--user", "--no-desktop", "--link-mode", "wrapper"])
+        .assert()
+        .success();
+
+    // 1. Upgrades reuse the wrapper without treating it as a conflict
+    tarsmith(home)
+        .args(["upgrade", "mytool"])
+        .arg(build_archive(
+            tar_dir.path(),
+            "mytool-2.0.tar",
+            &[launcher("2.0")],
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Conflict").not())
+        .stdout(predicate::str::contains("Created wrapper: mytool ->"));
+    assert!(run_launcher(home).contains("version=2.0"));
+
+    // 2. Uninstall recognises and removes it
+    tarsmith(home)
+        .args(["uninstall", "mytool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed wrapper:"));
+    assert!(!home.join(".local/bin/mytool").exists());
+}
+
+#[test]
+fn test_wrapper_options_validated() {
+    let temp_home = TempDir::new().expect("temp home");
+    let tar_dir = TempDir::new().expect("tar dir");
+    let home = temp_home.path();
+    let archive = build_archive(tar_dir.path(), "mytool-1.0.tar", &[launcher("1.0")]);
+
+    // 1. Wrapper settings without wrapper mode
+    tarsmith(home)
+        .arg(&archive)
+        .args(["--user", "--wrapper-env", "JAVA_HOME=/opt/jdk"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("InvalidSelection"));
+
+    // 2. Malformed assignments
+    tarsmith(home)
+        .arg(&archive)
+        .args([
+            "--user",
+            "--link-mode",
+            "wrapper",
+            "--wrapper-env",
+            "1BAD=x",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid environment variable name",
+        ));
+
+    assert!(!home.join(".local/tarsmith").exists());
+}
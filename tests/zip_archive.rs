@@ -0,0 +1,67 @@
+// tests/zip_archive.rs
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs::{self, File};
+use std::io::Write;
+
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+
+#[test]
+fn test_zip_installation_restores_exec_bits() {
+    // 1. Setup isolated environment
+    let temp_home = TempDir::new().expect("temp home");
+    let zip_dir = TempDir::new().expect("zip dir");
+
+    // 2. Create zip archive with an executable and a plain file
+    let archive_path = zip_dir.path().join("gradle-8.5-bin.zip");
+    let mut writer = zip::ZipWriter::new(File::create(&archive_path).expect("create zip"));
+    writer
+        .add_directory("gradle-8.5/bin/", SimpleFileOptions::default())
+        .expect("add dir");
+    writer
+        .start_file(
+            "gradle-8.5/bin/gradle",
+            SimpleFileOptions::default().unix_permissions(0o755),
+        )
+        .expect("start exe");
+    writer
+        .write_all(b"#!/bin/sh\necho gradle")
+        .expect("write exe");
+    writer
+        .start_file(
+            "gradle-8.5/LICENSE",
+            SimpleFileOptions::default().unix_permissions(0o644),
+        )
+        .expect("start license");
+    writer.write_all(b"license").expect("write license");
+    writer.finish().expect("finish zip");
+
+    // 3. Run installer
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_tarsmith"));
+    cmd.env("HOME", temp_home.path())
+        .arg(&archive_path)
+        .arg("--user")
+        .arg("--no-desktop");
+
+    // 4. Assertions
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Detected format: zip"))
+        .stdout(predicate::str::contains("Created symlink: gradle"));
+
+    let exe = temp_home
+        .path()
+        .join(".local/tarsmith/gradle/current/bin/gradle");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&exe)
+            .expect("exe extracted")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755, "exec bits not restored");
+    }
+    assert!(temp_home.path().join(".local/bin/gradle").exists());
+}